@@ -0,0 +1,345 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use crate::engine::Engine;
+use crate::neighbourhood::Neighbourhood;
+use crate::rule::Rule;
+
+// 팔레트를 지정하지 않았을 때의 색(죽은 셀, 살아 있는 셀, 죽어 가는 셀...)
+pub const DEFAULT_PALETTE: &str = "ffffff,000000,ff0000,ff8700,ffff00,00ff00,00ffff,0000ff,ff00ff";
+
+// GIF 애니메이션 한 프레임의 표시 시간(1/100초)
+const GIF_DELAY: u16 = 10;
+
+// 셀의 상태마다의 색
+#[derive(Clone)]
+pub struct Palette {
+    colours: Vec<[u8; 3]>,
+}
+
+impl Palette {
+    // 「ffffff,000000,ff0000」처럼 쉼표로 구분한 16진수의 색을 해석한다
+    // 앞에서부터 죽은 셀, 살아 있는 셀, 죽어 가는 셀(상태 2, 3, ...)의 색이다
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let colours = text
+            .split(',')
+            .map(|colour| {
+                let colour = colour.trim().trim_start_matches('#');
+                let value = u32::from_str_radix(colour, 16)
+                    .ok()
+                    .filter(|_| colour.len() == 6)
+                    .ok_or_else(|| format!("색 \"{colour}\"은(는) RRGGBB 형식이 아닙니다"))?;
+                Ok([(value >> 16) as u8, (value >> 8) as u8, value as u8])
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        if colours.len() < 2 {
+            return Err("팔레트에는 죽은 셀과 살아 있는 셀의 두 색이 필요합니다".to_string());
+        }
+        Ok(Self { colours })
+    }
+
+    // 상태의 수만큼의 색을 RGB의 나열로 반환한다. 모자라는 죽어 가는 셀의 색은 되풀이하여 쓴다
+    fn table(&self, states: u8) -> Vec<u8> {
+        let dying = self.colours.len().saturating_sub(2);
+        (0..states as usize)
+            .flat_map(|state| match state {
+                0 | 1 => self.colours[state],
+                _ if dying == 0 => self.colours[1],
+                _ => self.colours[2 + (state - 2) % dying],
+            })
+            .collect()
+    }
+}
+
+// 애니메이션의 저장 방법
+pub struct AnimationOptions {
+    pub path: String, // .gif로 끝나면 GIF 애니메이션, 그 밖에는 PNG 연번 파일의 접두사
+    pub generations: u64, // 진행하는 세대 수
+    pub every: u64,   // 몇 세대마다 한 프레임을 쓸지
+    pub cell_size: usize, // 셀 하나의 픽셀 수
+    pub palette: Palette,
+}
+
+// 필드의 region(x, y, 폭, 높이)을 셀마다 cell_size x cell_size 픽셀의 팔레트 번호로 그린다
+// indent가 0보다 크면 육각형 격자로 보고 홀수 행을 indent 픽셀 오른쪽으로 어긋나게 한다
+fn render_frame(
+    engine: &dyn Engine,
+    region: (i64, i64, usize, usize),
+    cell_size: usize,
+    indent: usize,
+) -> Vec<u8> {
+    let (x0, y0, width, height) = region;
+    let mut pixels = Vec::with_capacity((width * cell_size + indent) * height * cell_size);
+    for y in y0..y0 + height as i64 {
+        let mut row = Vec::with_capacity(width * cell_size + indent);
+        let odd_row = y.rem_euclid(2) == 1;
+        if odd_row {
+            row.extend(std::iter::repeat_n(0, indent));
+        }
+        for x in x0..x0 + width as i64 {
+            let state = engine.state(x, y);
+            row.extend(std::iter::repeat_n(state, cell_size));
+        }
+        if !odd_row {
+            row.extend(std::iter::repeat_n(0, indent));
+        }
+        for _ in 0..cell_size {
+            pixels.extend_from_slice(&row);
+        }
+    }
+    pixels
+}
+
+// 프레임의 폭과 높이(픽셀)와 육각형 격자에서 홀수 행을 어긋나게 하는 픽셀 수
+fn frame_size(
+    rule: &Rule,
+    region: (i64, i64, usize, usize),
+    options: &AnimationOptions,
+) -> (usize, usize, usize) {
+    let indent = match rule.neighbourhood() {
+        Neighbourhood::Hexagonal => options.cell_size / 2,
+        _ => 0,
+    };
+    (
+        region.2 * options.cell_size + indent,
+        region.3 * options.cell_size,
+        indent,
+    )
+}
+
+// 0세대부터 options.generations세대까지 every세대마다 region을 그려 세대와 함께 write에 넘긴다
+// 쓴 프레임의 수를 반환한다
+fn render_animation(
+    engine: &mut dyn Engine,
+    rule: &Rule,
+    region: (i64, i64, usize, usize),
+    options: &AnimationOptions,
+    mut write: impl FnMut(u64, Vec<u8>) -> Result<(), String>,
+) -> Result<usize, String> {
+    let (_, _, indent) = frame_size(rule, region, options);
+    let mut frames = 0;
+    let mut generation = 0;
+    loop {
+        write(
+            generation,
+            render_frame(engine, region, options.cell_size, indent),
+        )?;
+        frames += 1;
+
+        if generation + options.every > options.generations {
+            break;
+        }
+        engine.advance(rule, options.every);
+        generation += options.every;
+    }
+    Ok(frames)
+}
+
+// 애니메이션을 GIF로 writer에 쓴다. 쓴 프레임의 수를 반환한다
+fn encode_gif<W: Write>(
+    writer: W,
+    engine: &mut dyn Engine,
+    rule: &Rule,
+    region: (i64, i64, usize, usize),
+    options: &AnimationOptions,
+) -> Result<usize, String> {
+    let (width, height, _) = frame_size(rule, region, options);
+    let (Ok(gif_width), Ok(gif_height)) = (u16::try_from(width), u16::try_from(height)) else {
+        return Err(format!(
+            "{width}x{height} 픽셀은 GIF에 담을 수 없습니다 (최대 65535x65535)"
+        ));
+    };
+    let palette = options.palette.table(rule.states());
+    let mut encoder =
+        gif::Encoder::new(writer, gif_width, gif_height, &palette).map_err(|e| e.to_string())?;
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .map_err(|e| e.to_string())?;
+    render_animation(engine, rule, region, options, |_, pixels| {
+        let mut frame = gif::Frame::from_indexed_pixels(gif_width, gif_height, pixels, None);
+        frame.delay = GIF_DELAY;
+        encoder.write_frame(&frame).map_err(|e| e.to_string())
+    })
+}
+
+// 한 프레임을 팔레트 번호의 PNG로 writer에 쓴다
+fn encode_png<W: Write>(
+    writer: W,
+    width: usize,
+    height: usize,
+    palette: &[u8],
+    pixels: &[u8],
+) -> Result<(), String> {
+    let mut encoder = png::Encoder::new(writer, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Indexed);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_palette(palette.to_vec());
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(pixels))
+        .map_err(|e| e.to_string())
+}
+
+// 애니메이션을 options.path에 저장한다. 쓴 프레임의 수를 반환한다
+pub fn export_animation(
+    engine: &mut dyn Engine,
+    rule: &Rule,
+    region: (i64, i64, usize, usize),
+    options: &AnimationOptions,
+) -> Result<usize, String> {
+    let path = &options.path;
+    if path.to_ascii_lowercase().ends_with(".gif") {
+        let file = File::create(path).map_err(|e| format!("{path}에 쓸 수 없습니다: {e}"))?;
+        return encode_gif(BufWriter::new(file), engine, rule, region, options)
+            .map_err(|e| format!("{path}: {e}"));
+    }
+
+    // 「life.png」처럼 지정하면 「life」를 접두사로 쓴다
+    let prefix = path.strip_suffix(".png").unwrap_or(path);
+    let (width, height, _) = frame_size(rule, region, options);
+    let palette = options.palette.table(rule.states());
+    render_animation(engine, rule, region, options, |generation, pixels| {
+        let frame_path = format!("{prefix}_{generation:06}.png");
+        let file =
+            File::create(&frame_path).map_err(|e| format!("{frame_path}에 쓸 수 없습니다: {e}"))?;
+        encode_png(BufWriter::new(file), width, height, &palette, &pixels)
+            .map_err(|e| format!("{frame_path}: {e}"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::{Field, Topology};
+
+    // 6x6의 토러스 가운데에 가로의 블링커를 놓는다
+    fn blinker() -> Field {
+        let mut field = Field::new(6, 6, Topology::Torus);
+        for x in 1..4 {
+            field.set(x, 2, true);
+        }
+        field
+    }
+
+    fn options(generations: u64) -> AnimationOptions {
+        AnimationOptions {
+            path: String::new(),
+            generations,
+            every: 1,
+            cell_size: 2,
+            palette: Palette::parse("ffffff,000000,ff0000").unwrap(),
+        }
+    }
+
+    #[test]
+    fn encode_gif_in_memory() {
+        let mut data = Vec::new();
+        let frames = encode_gif(
+            &mut data,
+            &mut blinker(),
+            &Rule::default(),
+            (0, 0, 6, 6),
+            &options(2),
+        )
+        .unwrap();
+        assert_eq!(frames, 3);
+
+        let mut decode_options = gif::DecodeOptions::new();
+        decode_options.set_color_output(gif::ColorOutput::Indexed);
+        let mut decoder = decode_options.read_info(&data[..]).unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (12, 12));
+        assert_eq!(
+            decoder.global_palette().unwrap(),
+            [0xff, 0xff, 0xff, 0, 0, 0]
+        );
+        let mut buffers = Vec::new();
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!((frame.width, frame.height), (12, 12));
+            buffers.push(frame.buffer.to_vec());
+        }
+        assert_eq!(buffers.len(), 3);
+        // 가로와 세로가 번갈아 나타난다. 셀 하나는 2x2 픽셀이다
+        let pixel = |buffer: &Vec<u8>, x: usize, y: usize| buffer[y * 2 * 12 + x * 2];
+        assert_eq!(pixel(&buffers[0], 1, 2), 1);
+        assert_eq!(pixel(&buffers[0], 2, 1), 0);
+        assert_eq!(pixel(&buffers[1], 1, 2), 0);
+        assert_eq!(pixel(&buffers[1], 2, 1), 1);
+        assert_eq!(buffers[0], buffers[2]);
+    }
+
+    #[test]
+    fn encode_png_frames_in_memory() {
+        let rule = Rule::parse("B2/S/C3").unwrap();
+        let animation = options(2);
+        let (width, height, _) = frame_size(&rule, (0, 0, 6, 6), &animation);
+        let palette = animation.palette.table(rule.states());
+        let mut files = Vec::new();
+        let frames = render_animation(
+            &mut blinker(),
+            &rule,
+            (0, 0, 6, 6),
+            &animation,
+            |generation, pixels| {
+                let mut data = Vec::new();
+                encode_png(&mut data, width, height, &palette, &pixels)?;
+                files.push((generation, data));
+                Ok(())
+            },
+        )
+        .unwrap();
+        assert_eq!(frames, 3);
+        assert_eq!(
+            files
+                .iter()
+                .map(|(generation, _)| *generation)
+                .collect::<Vec<_>>(),
+            [0, 1, 2]
+        );
+
+        for (generation, data) in &files {
+            let mut reader = png::Decoder::new(&data[..]).read_info().unwrap();
+            let info = reader.info();
+            assert_eq!((info.width, info.height), (12, 12));
+            assert_eq!(info.color_type, png::ColorType::Indexed);
+            // Generations 규칙에서는 죽어 가는 셀의 색까지 팔레트에 들어간다
+            assert_eq!(
+                info.palette.as_deref().unwrap(),
+                [0xff, 0xff, 0xff, 0, 0, 0, 0xff, 0, 0]
+            );
+            let mut pixels = vec![0; reader.output_buffer_size()];
+            reader.next_frame(&mut pixels).unwrap();
+            // 1세대째에는 처음의 블링커가 모두 죽어 가는 셀(상태 2)이 된다
+            if *generation == 1 {
+                assert_eq!(pixels[2 * 2 * 12 + 2], 2);
+            }
+        }
+    }
+
+    #[test]
+    fn hexagonal_frames_are_indented() {
+        let rule = Rule::parse("B2/S34H").unwrap();
+        let mut animation = options(0);
+        animation.cell_size = 4;
+        assert_eq!(frame_size(&rule, (0, 0, 6, 5), &animation), (26, 20, 2));
+        assert_eq!(
+            frame_size(&Rule::default(), (0, 0, 6, 5), &animation),
+            (24, 20, 0)
+        );
+    }
+
+    #[test]
+    fn reject_bad_palettes() {
+        assert!(Palette::parse("#ffffff, #000000").is_ok());
+        for text in [
+            "",
+            "ffffff",
+            "fffff,000000",
+            "ffffff,0000000",
+            "gggggg,000000",
+            "ffffff,,000000",
+            "ffffff;000000",
+        ] {
+            assert!(Palette::parse(text).is_err(), "{text}");
+        }
+    }
+}
@@ -0,0 +1,289 @@
+use std::thread;
+
+use crate::engine::Engine;
+use crate::field::Topology;
+use crate::rule::Rule;
+
+// 한 워드에 담는 셀의 수
+const BITS: usize = u64::BITS as usize;
+
+// 셀 하나를 1비트로 하여 한 행을 u64의 나열로 보유하는 필드
+// 64셀분의 이웃 수를 비트 단위의 가산기로 한 번에 센다
+pub struct BitField {
+    width: usize,
+    height: usize,
+    topology: Topology,
+    words_per_row: usize,
+    words: Vec<u64>, // u64[height][words_per_row], x번째 셀은 x / 64번째 워드의 x % 64번째 비트
+    next_words: Vec<u64>,
+    threads: usize, // 행을 나누어 계산하는 스레드의 수
+}
+
+impl BitField {
+    pub fn new(width: usize, height: usize, topology: Topology, threads: usize) -> Self {
+        let words_per_row = width.div_ceil(BITS);
+        Self {
+            width,
+            height,
+            topology,
+            words_per_row,
+            words: vec![0; words_per_row * height],
+            next_words: vec![0; words_per_row * height],
+            threads: threads.max(1),
+        }
+    }
+
+    fn row(&self, y: usize) -> &[u64] {
+        &self.words[y * self.words_per_row..(y + 1) * self.words_per_row]
+    }
+
+    // 행의 마지막 워드 중 필드 밖에 해당하는 비트를 지우는 마스크
+    fn last_word_mask(&self) -> u64 {
+        match self.width % BITS {
+            0 => !0,
+            n => (1 << n) - 1,
+        }
+    }
+
+    // 위상에 따라 y행의 위(dy = -1) 또는 아래(dy = 1) 행을 out에 복사한다
+    // 가장자리 밖이 죽은 셀이면 0으로 채운다
+    fn neighbour_row(&self, y: usize, dy: i64, out: &mut [u64]) {
+        match self.topology.wrap_row(self.height, y as i64 + dy) {
+            None => out.fill(0),
+            Some((y, false)) => out.copy_from_slice(self.row(y)),
+            Some((y, true)) => {
+                // 클라인 병에서는 좌우가 뒤집힌 행과 이어진다
+                out.fill(0);
+                let row = self.row(y);
+                for x in 0..self.width {
+                    if row[x / BITS] >> (x % BITS) & 1 != 0 {
+                        let flipped_x = self.width - 1 - x;
+                        out[flipped_x / BITS] |= 1 << (flipped_x % BITS);
+                    }
+                }
+            }
+        }
+    }
+
+    // 각 셀에 왼쪽 이웃(x - 1)의 값이 오도록 행을 옮긴다
+    fn shift_from_left(&self, row: &[u64], out: &mut [u64]) {
+        let mut carry = if self.topology.wraps_horizontally() {
+            let x = self.width - 1;
+            row[x / BITS] >> (x % BITS) & 1
+        } else {
+            0
+        };
+        for (word, shifted) in row.iter().zip(out.iter_mut()) {
+            *shifted = word << 1 | carry;
+            carry = word >> (BITS - 1);
+        }
+    }
+
+    // 각 셀에 오른쪽 이웃(x + 1)의 값이 오도록 행을 옮긴다
+    fn shift_from_right(&self, row: &[u64], out: &mut [u64]) {
+        for i in 0..row.len() {
+            let next = if i + 1 < row.len() { row[i + 1] } else { 0 };
+            out[i] = row[i] >> 1 | next << (BITS - 1);
+        }
+        if self.topology.wraps_horizontally() {
+            let x = self.width - 1;
+            out[x / BITS] |= (row[0] & 1) << (x % BITS);
+        }
+    }
+
+    // first_row부터 next의 행 수만큼 다음 세대를 계산하여 next에 써넣는다
+    fn step_rows(&self, rule: &Rule, first_row: usize, next: &mut [u64]) {
+        let words_per_row = self.words_per_row;
+        let mask = self.last_word_mask();
+
+        // 인접하는 살아 있는 셀의 수가 n일 때의 탄생·생존 여부
+        let birth: Vec<bool> = (0..=8).map(|n| rule.next_state(false, n)).collect();
+        let survival: Vec<bool> = (0..=8).map(|n| rule.next_state(true, n)).collect();
+
+        // 위, 현재, 아래 행과 그것을 좌우로 옮긴 행을 담는 작업용 버퍼
+        let mut rows = vec![vec![0_u64; words_per_row]; 3];
+        let mut from_left = vec![vec![0_u64; words_per_row]; 3];
+        let mut from_right = vec![vec![0_u64; words_per_row]; 3];
+
+        for (i, next_row) in next.chunks_mut(words_per_row).enumerate() {
+            let y = first_row + i;
+            self.neighbour_row(y, -1, &mut rows[0]);
+            rows[1].copy_from_slice(self.row(y));
+            self.neighbour_row(y, 1, &mut rows[2]);
+            for r in 0..3 {
+                self.shift_from_left(&rows[r], &mut from_left[r]);
+                self.shift_from_right(&rows[r], &mut from_right[r]);
+            }
+
+            for w in 0..words_per_row {
+                let neighbours = [
+                    from_left[0][w],
+                    rows[0][w],
+                    from_right[0][w],
+                    from_left[1][w],
+                    from_right[1][w],
+                    from_left[2][w],
+                    rows[2][w],
+                    from_right[2][w],
+                ];
+
+                // 비트마다 독립된 4비트 카운터(count[0]이 최하위 비트)에 8개의 이웃을 더한다
+                let mut count = [0_u64; 4];
+                for neighbour in neighbours {
+                    let mut carry = neighbour;
+                    for bit in count.iter_mut() {
+                        let next_carry = *bit & carry;
+                        *bit ^= carry;
+                        carry = next_carry;
+                    }
+                }
+
+                // 이웃 수가 n인 비트를 골라내 규칙을 적용한다
+                let alive = rows[1][w];
+                let mut result = 0;
+                for n in 0..=8 {
+                    if !birth[n] && !survival[n] {
+                        continue;
+                    }
+                    let mut equals = !0;
+                    for (b, bit) in count.iter().enumerate() {
+                        equals &= if n >> b & 1 != 0 { *bit } else { !*bit };
+                    }
+                    if birth[n] {
+                        result |= equals & !alive;
+                    }
+                    if survival[n] {
+                        result |= equals & alive;
+                    }
+                }
+                next_row[w] = result;
+            }
+            next_row[words_per_row - 1] &= mask;
+        }
+    }
+}
+
+impl Engine for BitField {
+    fn name(&self) -> String {
+        format!(
+            "bitboard x{} {}x{} {}",
+            self.threads,
+            self.width,
+            self.height,
+            self.topology.name()
+        )
+    }
+
+    fn step(&mut self, rule: &Rule) {
+        let mut next_words = std::mem::take(&mut self.next_words);
+        if self.threads == 1 {
+            self.step_rows(rule, 0, &mut next_words);
+        } else {
+            // 행을 띠 모양으로 나누어 스레드마다 계산한다
+            let band_rows = self.height.div_ceil(self.threads);
+            let this = &*self;
+            thread::scope(|scope| {
+                for (band, next) in next_words
+                    .chunks_mut(band_rows * this.words_per_row)
+                    .enumerate()
+                {
+                    scope.spawn(move || this.step_rows(rule, band * band_rows, next));
+                }
+            });
+        }
+        self.next_words = std::mem::replace(&mut self.words, next_words);
+    }
+
+    fn get(&self, x: i64, y: i64) -> bool {
+        self.topology
+            .wrap(self.width, self.height, x, y)
+            .is_some_and(|(x, y)| self.row(y)[x / BITS] >> (x % BITS) & 1 != 0)
+    }
+
+    fn set(&mut self, x: i64, y: i64, alive: bool) {
+        if let Some((x, y)) = self.topology.wrap(self.width, self.height, x, y) {
+            let word = &mut self.words[y * self.words_per_row + x / BITS];
+            if alive {
+                *word |= 1 << (x % BITS);
+            } else {
+                *word &= !(1 << (x % BITS));
+            }
+        }
+    }
+
+    fn size(&self) -> Option<(usize, usize)> {
+        Some((self.width, self.height))
+    }
+
+    fn live_cells(&self) -> Vec<(i64, i64)> {
+        let mut cells = Vec::new();
+        for y in 0..self.height {
+            for (i, word) in self.row(y).iter().enumerate() {
+                let mut word = *word;
+                while word != 0 {
+                    let x = i * BITS + word.trailing_zeros() as usize;
+                    cells.push((x as i64, y as i64));
+                    word &= word - 1;
+                }
+            }
+        }
+        cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::Field;
+
+    #[test]
+    fn matches_grid_engine() {
+        let topologies = [
+            Topology::Torus,
+            Topology::Plane,
+            Topology::KleinBottle,
+            Topology::Cylinder,
+        ];
+        // 한 워드에 들어가는 폭, 딱 한 워드의 폭, 워드를 넘어 끝이 남는 홀수의 폭
+        let sizes = [(37, 23), (64, 16), (131, 19)];
+        let rules = [Rule::default(), Rule::parse("B36/S23").unwrap()];
+        let mut seed: u64 = 0x9e37_79b9_7f4a_7c15;
+        for topology in topologies {
+            for (width, height) in sizes {
+                for rule in &rules {
+                    let mut field = Field::new(width, height, topology);
+                    let mut bitfields = [
+                        BitField::new(width, height, topology, 1),
+                        BitField::new(width, height, topology, 4),
+                    ];
+                    // 밀도 약 0.4의 랜덤 수프를 xorshift로 만든다
+                    for y in 0..height as i64 {
+                        for x in 0..width as i64 {
+                            seed ^= seed << 13;
+                            seed ^= seed >> 7;
+                            seed ^= seed << 17;
+                            let alive = seed % 5 < 2;
+                            field.set(x, y, alive);
+                            for bitfield in &mut bitfields {
+                                bitfield.set(x, y, alive);
+                            }
+                        }
+                    }
+                    for generation in 1..=30 {
+                        field.step(rule);
+                        let expected = field.live_cells();
+                        for bitfield in &mut bitfields {
+                            bitfield.step(rule);
+                            assert_eq!(
+                                bitfield.live_cells(),
+                                expected,
+                                "{} {rule} {generation}세대",
+                                bitfield.name()
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use crate::neighbourhood::Neighbourhood;
+use crate::rule::Rule;
+
+// 명령줄에서 고르는 엔진의 종류
+#[derive(Clone, Copy, PartialEq)]
+pub enum EngineKind {
+    Grid,     // 셀마다 이웃을 세는 원래의 방식
+    Bitboard, // 64셀씩 비트 연산으로 계산하는 방식
+    HashLife, // 사분 트리로 2^n 세대를 한 번에 진행하는 끝이 없는 평면
+    Sparse,   // 살아 있는 셀의 좌표만 보유하는 끝이 없는 평면
+}
+
+impl EngineKind {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().as_str() {
+            "grid" => Ok(EngineKind::Grid),
+            "bitboard" => Ok(EngineKind::Bitboard),
+            "hashlife" => Ok(EngineKind::HashLife),
+            "sparse" => Ok(EngineKind::Sparse),
+            _ => Err(format!(
+                "알 수 없는 엔진 \"{name}\": grid, bitboard, hashlife, sparse 중 하나를 지정하세요"
+            )),
+        }
+    }
+
+    // 규칙을 다룰 수 있는지 여부
+    // grid 이외는 무어 근방(8셀)의 죽어 가는 상태가 없는 규칙만 계산할 수 있다
+    pub fn supports(&self, rule: &Rule) -> bool {
+        *self == EngineKind::Grid
+            || (rule.states() == 2 && rule.neighbourhood() == Neighbourhood::Moore(1))
+    }
+
+    // 필드에 가장자리가 없는지 여부
+    pub fn is_unbounded(&self) -> bool {
+        matches!(self, EngineKind::HashLife | EngineKind::Sparse)
+    }
+}
+
+// 세대를 진행시키는 방식(백엔드)마다 구현하는 공통의 인터페이스
+pub trait Engine {
+    // 표시용 이름
+    fn name(&self) -> String;
+
+    // 1세대만큼 시뮬레이션을 진행한다
+    fn step(&mut self, rule: &Rule);
+
+    // generations 세대만큼 시뮬레이션을 진행한다
+    fn advance(&mut self, rule: &Rule, generations: u64) {
+        for _ in 0..generations {
+            self.step(rule);
+        }
+    }
+
+    // (x, y)의 셀이 살아 있는지 여부를 반환한다
+    // 유한한 필드에서는 범위 밖의 좌표를 위상에 따라 필드 안으로 옮긴다
+    fn get(&self, x: i64, y: i64) -> bool;
+
+    // (x, y)의 셀을 살리거나 죽인다. 좌표는 get()과 같이 다룬다
+    fn set(&mut self, x: i64, y: i64, alive: bool);
+
+    // (x, y)의 셀의 상태(0: 죽은 셀, 1: 살아 있는 셀, 2 이상: Generations 규칙에서 죽어 가는 셀)
+    fn state(&self, x: i64, y: i64) -> u8 {
+        self.get(x, y) as u8
+    }
+
+    // (x, y)의 셀의 상태를 바꾼다. 죽어 가는 상태를 다루지 않는 엔진은 살아 있는 셀(1)인지만 본다
+    fn set_state(&mut self, x: i64, y: i64, state: u8) {
+        self.set(x, y, state == 1);
+    }
+
+    // 유한한 필드라면 (폭, 높이)를, 끝이 없는 우주라면 None을 반환한다
+    fn size(&self) -> Option<(usize, usize)>;
+
+    // 살아 있는 셀의 좌표를 모두 반환한다
+    fn live_cells(&self) -> Vec<(i64, i64)>;
+
+    // 죽어 가는 셀의 좌표와 상태를 모두 반환한다
+    fn dying_cells(&self) -> Vec<(i64, i64, u8)> {
+        Vec::new()
+    }
+}
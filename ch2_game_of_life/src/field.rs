@@ -0,0 +1,327 @@
+use crate::engine::Engine;
+use crate::rule::Rule;
+
+// 필드의 가장자리를 넘은 이웃을 어떻게 다룰지를 정하는 위상
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Topology {
+    Torus,       // 상하좌우 모두 반대쪽과 이어진다
+    Plane,       // 가장자리 밖은 항상 죽은 셀이다
+    KleinBottle, // 좌우는 그대로, 상하는 좌우가 뒤집혀서 이어진다
+    Cylinder,    // 좌우만 이어지고 상하의 밖은 죽은 셀이다
+}
+
+impl Topology {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().as_str() {
+            "torus" => Ok(Topology::Torus),
+            "plane" | "dead" => Ok(Topology::Plane),
+            "klein" | "kleinbottle" => Ok(Topology::KleinBottle),
+            "cylinder" => Ok(Topology::Cylinder),
+            _ => Err(format!(
+                "알 수 없는 위상 \"{name}\": torus, plane, klein, cylinder 중 하나를 지정하세요"
+            )),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Topology::Torus => "torus",
+            Topology::Plane => "plane",
+            Topology::KleinBottle => "klein",
+            Topology::Cylinder => "cylinder",
+        }
+    }
+
+    // 좌우의 가장자리가 반대쪽과 이어지는지 여부
+    pub fn wraps_horizontally(&self) -> bool {
+        *self != Topology::Plane
+    }
+
+    // 필드 밖일 수도 있는 행 y를 필드 안의 행과 그 행의 좌우가 뒤집히는지 여부로 바꾼다
+    // 죽은 셀로 다루는 가장자리 밖이면 None을 반환한다
+    pub fn wrap_row(&self, height: usize, y: i64) -> Option<(usize, bool)> {
+        let height = height as i64;
+        match self {
+            Topology::Torus => Some((y.rem_euclid(height) as usize, false)),
+            Topology::Plane | Topology::Cylinder => {
+                (0..height).contains(&y).then_some((y as usize, false))
+            }
+            // 상하의 가장자리를 넘을 때마다 좌우가 뒤집힌다
+            Topology::KleinBottle => {
+                Some((y.rem_euclid(height) as usize, y.div_euclid(height) % 2 != 0))
+            }
+        }
+    }
+
+    // 필드 밖일 수도 있는 좌표를 필드 안의 좌표로 바꾼다
+    // 죽은 셀로 다루는 가장자리 밖이면 None을 반환한다
+    pub fn wrap(&self, width: usize, height: usize, x: i64, y: i64) -> Option<(usize, usize)> {
+        let (y, flipped) = self.wrap_row(height, y)?;
+        let width = width as i64;
+        let x = if flipped { width - 1 - x } else { x };
+        if self.wraps_horizontally() {
+            Some((x.rem_euclid(width) as usize, y))
+        } else {
+            (0..width).contains(&x).then_some((x as usize, y))
+        }
+    }
+}
+
+// 크기와 위상을 실행 시에 정하는 라이프 게임의 필드
+pub struct Field {
+    pub width: usize,
+    pub height: usize,
+    pub topology: Topology,
+    pub cells: Vec<u8>, // u8[height][width], 셀의 상태
+}
+
+impl Field {
+    pub fn new(width: usize, height: usize, topology: Topology) -> Self {
+        Self {
+            width,
+            height,
+            topology,
+            cells: vec![0; width * height],
+        }
+    }
+
+    pub fn get_cell(&self, x: usize, y: usize) -> bool {
+        self.cells[y * self.width + x] == 1
+    }
+
+    pub fn set_cell(&mut self, x: usize, y: usize, alive: bool) {
+        self.cells[y * self.width + x] = alive as u8;
+    }
+
+    // 필드 밖일 수도 있는 좌표를 위상에 따라 필드 안의 좌표로 바꾼다
+    pub fn wrap(&self, x: i64, y: i64) -> Option<(usize, usize)> {
+        self.topology.wrap(self.width, self.height, x, y)
+    }
+
+    // [4-2]대상 셀과 인접하는 살아 있는 셀의 수를 가져오는 함수를 선언한다
+    // offsets는 근방에 포함되는 이웃의 상대 좌표로, 중심 셀은 포함하지 않는다
+    pub fn get_living_cells_count(&self, x_: i64, y_: i64, offsets: &[(i64, i64)]) -> usize {
+        let mut count = 0;
+        for (dx, dy) in offsets {
+            // [4-2-12]위상에 따라 옮긴 대상 셀이 살아 있으면 1을, 죽어 있으면 0을 가산한다
+            if let Some((wrapped_x, wrapped_y)) = self.wrap(x_ + dx, y_ + dy)
+                && self.get_cell(wrapped_x, wrapped_y)
+            {
+                count += 1;
+            }
+        }
+        count // [4-2-13]살아 있는 셀의 수를 반환한다
+    }
+}
+
+impl Engine for Field {
+    fn name(&self) -> String {
+        format!(
+            "grid {}x{} {}",
+            self.width,
+            self.height,
+            self.topology.name()
+        )
+    }
+
+    // [4-3]1스텝만큼의 시뮬레이션을 실행하는 함수를 선언한다
+    fn step(&mut self, rule: &Rule) {
+        // [4-3-1]다음 세대의 필드를 선언한다
+        let mut next_cells = vec![0; self.width * self.height];
+        // 육각형 격자에서는 행의 홀짝에 따라 이웃의 위치가 다르다
+        let offsets = rule.neighbourhood().offsets();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                // [4-3-4]대상 셀과 인접하는 살아 있는 셀의 수를 선언한다
+                let living_cell_count =
+                    self.get_living_cells_count(x as i64, y as i64, &offsets[y % 2]);
+                // [4-3-5]인접하는 살아 있는 셀의 수와 규칙으로 다음 세대의 상태를 정한다
+                next_cells[y * self.width + x] =
+                    rule.next_cell(self.cells[y * self.width + x], living_cell_count);
+            }
+        }
+        // [4-3-13]다음 스텝의 필드를 현재 필드에 복사한다
+        self.cells.clone_from_slice(&next_cells);
+    }
+
+    fn get(&self, x: i64, y: i64) -> bool {
+        self.wrap(x, y).is_some_and(|(x, y)| self.get_cell(x, y))
+    }
+
+    fn set(&mut self, x: i64, y: i64, alive: bool) {
+        if let Some((x, y)) = self.wrap(x, y) {
+            self.set_cell(x, y, alive);
+        }
+    }
+
+    fn state(&self, x: i64, y: i64) -> u8 {
+        self.wrap(x, y)
+            .map_or(0, |(x, y)| self.cells[y * self.width + x])
+    }
+
+    fn set_state(&mut self, x: i64, y: i64, state: u8) {
+        if let Some((x, y)) = self.wrap(x, y) {
+            self.cells[y * self.width + x] = state;
+        }
+    }
+
+    fn size(&self) -> Option<(usize, usize)> {
+        Some((self.width, self.height))
+    }
+
+    fn live_cells(&self) -> Vec<(i64, i64)> {
+        (0..self.cells.len())
+            .filter(|i| self.cells[*i] == 1)
+            .map(|i| ((i % self.width) as i64, (i / self.width) as i64))
+            .collect()
+    }
+
+    fn dying_cells(&self) -> Vec<(i64, i64, u8)> {
+        (0..self.cells.len())
+            .filter(|i| self.cells[*i] >= 2)
+            .map(|i| {
+                (
+                    (i % self.width) as i64,
+                    (i / self.width) as i64,
+                    self.cells[i],
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neighbourhood::Neighbourhood;
+
+    // 왼쪽 위로 가는 글라이더
+    const GLIDER: [(i64, i64); 5] = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+
+    fn sorted(mut cells: Vec<(i64, i64)>) -> Vec<(i64, i64)> {
+        cells.sort_unstable();
+        cells
+    }
+
+    #[test]
+    fn wrap_cells_across_each_edge() {
+        let torus = Topology::Torus;
+        assert_eq!(torus.wrap(5, 4, -1, -1), Some((4, 3)));
+        assert_eq!(torus.wrap(5, 4, 5, 4), Some((0, 0)));
+        assert_eq!(torus.wrap(5, 4, 12, -6), Some((2, 2)));
+
+        let plane = Topology::Plane;
+        assert_eq!(plane.wrap(5, 4, 4, 3), Some((4, 3)));
+        for (x, y) in [(-1, 0), (5, 0), (0, -1), (0, 4)] {
+            assert_eq!(plane.wrap(5, 4, x, y), None);
+        }
+
+        let cylinder = Topology::Cylinder;
+        assert_eq!(cylinder.wrap(5, 4, -1, 2), Some((4, 2)));
+        assert_eq!(cylinder.wrap(5, 4, 5, 3), Some((0, 3)));
+        assert_eq!(cylinder.wrap(5, 4, 0, -1), None);
+        assert_eq!(cylinder.wrap(5, 4, 0, 4), None);
+
+        // 상하의 가장자리를 넘으면 좌우가 뒤집히고, 두 번 넘으면 원래대로 돌아온다
+        let klein = Topology::KleinBottle;
+        assert_eq!(klein.wrap_row(4, 3), Some((3, false)));
+        assert_eq!(klein.wrap_row(4, -1), Some((3, true)));
+        assert_eq!(klein.wrap_row(4, 4), Some((0, true)));
+        assert_eq!(klein.wrap_row(4, 8), Some((0, false)));
+        assert_eq!(klein.wrap_row(4, -5), Some((3, false)));
+        assert_eq!(klein.wrap(5, 4, 0, -1), Some((4, 3)));
+        assert_eq!(klein.wrap(5, 4, 1, 4), Some((3, 0)));
+        assert_eq!(klein.wrap(5, 4, -1, 4), Some((0, 0)));
+        assert_eq!(klein.wrap(5, 4, 5, 5), Some((4, 1)));
+        // 좌우의 가장자리는 뒤집히지 않고 그대로 이어진다
+        assert_eq!(klein.wrap(5, 4, -1, 2), Some((4, 2)));
+    }
+
+    #[test]
+    fn neighbours_across_the_corner() {
+        // 오른쪽 아래 구석의 셀이 왼쪽 위 구석의 이웃으로 세어지는지는 위상에 따라 다르다
+        let offsets = &Neighbourhood::Moore(1).offsets()[0];
+        for (topology, expected) in [
+            (Topology::Torus, 1),
+            (Topology::Plane, 0),
+            (Topology::Cylinder, 0),
+            (Topology::KleinBottle, 1),
+        ] {
+            let mut field = Field::new(5, 4, topology);
+            field.set_cell(4, 3, true);
+            assert_eq!(
+                field.get_living_cells_count(0, 0, offsets),
+                expected,
+                "{topology:?}"
+            );
+        }
+        // 클라인의 병에서는 위의 가장자리 너머에 좌우가 뒤집힌 아래의 행이 있다
+        for (topology, left, right) in [(Topology::Torus, 0, 1), (Topology::KleinBottle, 1, 0)] {
+            let mut field = Field::new(5, 4, topology);
+            field.set_cell(3, 3, true);
+            assert_eq!(
+                field.get_living_cells_count(0, 0, offsets),
+                left,
+                "{topology:?}"
+            );
+            assert_eq!(
+                field.get_living_cells_count(4, 0, offsets),
+                right,
+                "{topology:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn glider_leaves_the_plane() {
+        let rule = Rule::default();
+        let mut field = Field::new(8, 8, Topology::Plane);
+        for (x, y) in GLIDER {
+            field.set(x + 3, y + 3, true);
+        }
+        for _ in 0..40 {
+            field.step(&rule);
+        }
+        // 가장자리에 부딪친 글라이더는 블록이 되어 남는다
+        assert_eq!(sorted(field.live_cells()), [(6, 6), (6, 7), (7, 6), (7, 7)]);
+    }
+
+    #[test]
+    fn glider_crosses_the_klein_seam_mirrored() {
+        let rule = Rule::default();
+        let (width, height) = (12, 10);
+        let mut klein = Field::new(width, height, Topology::KleinBottle);
+        let mut torus = Field::new(width, height, Topology::Torus);
+        // 가장자리가 없는 것과 같은 넓은 평면에서 진행시킨 글라이더를 접어 넣은 것과 비교한다
+        let mut plane = Field::new(64, 64, Topology::Plane);
+        for (x, y) in GLIDER {
+            klein.set(x + 2, y + 2, true);
+            torus.set(x + 2, y + 2, true);
+            plane.set(x + 2, y + 2, true);
+        }
+        for generation in 1..=48 {
+            klein.step(&rule);
+            torus.step(&rule);
+            plane.step(&rule);
+            let folded: Vec<_> = plane
+                .live_cells()
+                .into_iter()
+                .filter_map(|(x, y)| Topology::KleinBottle.wrap(width, height, x, y))
+                .map(|(x, y)| (x as i64, y as i64))
+                .collect();
+            assert_eq!(
+                sorted(klein.live_cells()),
+                sorted(folded),
+                "{generation}세대"
+            );
+        }
+        // 아래의 가장자리를 한 번 완전히 넘은 글라이더는 토러스의 것을 좌우로 뒤집은 모양이다
+        let mirrored: Vec<_> = torus
+            .live_cells()
+            .into_iter()
+            .map(|(x, y)| (width as i64 - 1 - x, y))
+            .collect();
+        assert_eq!(sorted(klein.live_cells()), sorted(mirrored));
+        assert_ne!(sorted(klein.live_cells()), sorted(torus.live_cells()));
+    }
+}
@@ -0,0 +1,435 @@
+use std::collections::HashMap;
+
+use crate::engine::Engine;
+use crate::rule::Rule;
+
+type NodeId = u32;
+
+// 죽은 셀과 살아 있는 셀을 나타내는 레벨 0의 노드
+const DEAD: NodeId = 0;
+const ALIVE: NodeId = 1;
+
+// 노드의 수가 이것을 넘으면 루트에서 닿지 않는 노드를 버린다
+const MAX_NODES: usize = 1 << 22;
+
+// 레벨 k의 노드는 2^k x 2^k의 정사각형을 나타낸다
+// 같은 내용의 노드는 하나만 만들어 공유한다
+#[derive(Clone)]
+struct Node {
+    children: [NodeId; 4], // 왼쪽 위, 오른쪽 위, 왼쪽 아래, 오른쪽 아래
+    level: u8,
+    population: u64,
+}
+
+// 사분 트리와 결과의 메모화로 2^n 세대를 한 번에 진행시키는 끝이 없는 평면
+pub struct HashLife {
+    nodes: Vec<Node>,
+    lookup: HashMap<[NodeId; 4], NodeId>,
+    results: HashMap<(NodeId, u8), NodeId>, // (노드, j) → 중앙을 2^j 세대 진행한 노드
+    results_rule: Option<Rule>,             // results를 계산했을 때의 규칙
+    empty: Vec<NodeId>,                     // 레벨마다의 빈 노드
+    root: NodeId,
+    origin: (i64, i64), // 루트의 왼쪽 위 좌표
+}
+
+impl HashLife {
+    pub fn new() -> Self {
+        let leaf = |population| Node {
+            children: [DEAD; 4],
+            level: 0,
+            population,
+        };
+        let mut hashlife = Self {
+            nodes: vec![leaf(0), leaf(1)],
+            lookup: HashMap::new(),
+            results: HashMap::new(),
+            results_rule: None,
+            empty: vec![DEAD],
+            root: DEAD,
+            origin: (-4, -4),
+        };
+        hashlife.root = hashlife.empty_node(3);
+        hashlife
+    }
+
+    fn level(&self, node: NodeId) -> u8 {
+        self.nodes[node as usize].level
+    }
+
+    fn population_of(&self, node: NodeId) -> u64 {
+        self.nodes[node as usize].population
+    }
+
+    fn children(&self, node: NodeId) -> [NodeId; 4] {
+        self.nodes[node as usize].children
+    }
+
+    // 네 개의 자식으로 이루어진 노드를 반환한다. 이미 있으면 그것을 공유한다
+    fn join(&mut self, children: [NodeId; 4]) -> NodeId {
+        if let Some(node) = self.lookup.get(&children) {
+            return *node;
+        }
+        let node = self.nodes.len() as NodeId;
+        self.nodes.push(Node {
+            children,
+            level: self.level(children[0]) + 1,
+            population: children.iter().map(|c| self.population_of(*c)).sum(),
+        });
+        self.lookup.insert(children, node);
+        node
+    }
+
+    fn empty_node(&mut self, level: u8) -> NodeId {
+        while self.empty.len() <= level as usize {
+            let empty = *self.empty.last().unwrap();
+            let node = self.join([empty; 4]);
+            self.empty.push(node);
+        }
+        self.empty[level as usize]
+    }
+
+    // 노드의 중앙 절반을 나타내는 한 레벨 낮은 노드를 반환한다
+    fn centre(&mut self, node: NodeId) -> NodeId {
+        let [nw, ne, sw, se] = self.children(node);
+        self.join([
+            self.children(nw)[3],
+            self.children(ne)[2],
+            self.children(sw)[1],
+            self.children(se)[0],
+        ])
+    }
+
+    // 주위를 빈 셀로 둘러싸 루트를 한 레벨 넓힌다
+    fn expand(&mut self) {
+        let level = self.level(self.root);
+        let [nw, ne, sw, se] = self.children(self.root);
+        let empty = self.empty_node(level - 1);
+        let children = [
+            self.join([empty, empty, empty, nw]),
+            self.join([empty, empty, ne, empty]),
+            self.join([empty, sw, empty, empty]),
+            self.join([se, empty, empty, empty]),
+        ];
+        self.root = self.join(children);
+        let half = 1_i64 << (level - 1);
+        self.origin = (self.origin.0 - half, self.origin.1 - half);
+    }
+
+    // 레벨 2(4x4)의 노드의 중앙 2x2를 1세대 진행한 노드를 반환한다
+    fn life_4x4(&mut self, node: NodeId, rule: &Rule) -> NodeId {
+        let cell = |x: usize, y: usize| {
+            let quadrant = self.children(node)[y / 2 * 2 + x / 2];
+            self.children(quadrant)[y % 2 * 2 + x % 2] == ALIVE
+        };
+        let mut next = [DEAD; 4];
+        for (i, (x, y)) in [(1, 1), (2, 1), (1, 2), (2, 2)].into_iter().enumerate() {
+            let mut living_cell_count = 0;
+            for ny in y - 1..=y + 1 {
+                for nx in x - 1..=x + 1 {
+                    if (nx, ny) != (x, y) && cell(nx, ny) {
+                        living_cell_count += 1;
+                    }
+                }
+            }
+            if rule.next_state(cell(x, y), living_cell_count) {
+                next[i] = ALIVE;
+            }
+        }
+        self.join(next)
+    }
+
+    // 레벨 k의 노드의 중앙(레벨 k - 1)을 2^j 세대 진행한 노드를 반환한다 (j <= k - 2)
+    fn successor(&mut self, node: NodeId, j: u8, rule: &Rule) -> NodeId {
+        let level = self.level(node);
+        let j = j.min(level - 2);
+        if self.population_of(node) == 0 {
+            return self.empty_node(level - 1);
+        }
+        if let Some(result) = self.results.get(&(node, j)) {
+            return *result;
+        }
+
+        let result = if level == 2 {
+            self.life_4x4(node, rule)
+        } else {
+            // 서로 겹치는 9개의 부분 노드를 각각 진행시킨다
+            let [a, b, c, d] = self.children(node);
+            let [_, ab, ac, ad] = self.children(a);
+            let [ba, _, bc, bd] = self.children(b);
+            let [ca, cb, _, cd] = self.children(c);
+            let [da, db, dc, _] = self.children(d);
+
+            let mut parts = [DEAD; 9];
+            for (i, part) in [
+                a,
+                self.join([ab, ba, ad, bc]),
+                b,
+                self.join([ac, ad, ca, cb]),
+                self.join([ad, bc, cb, da]),
+                self.join([bc, bd, da, db]),
+                c,
+                self.join([cb, da, cd, dc]),
+                d,
+            ]
+            .into_iter()
+            .enumerate()
+            {
+                parts[i] = self.successor(part, j, rule);
+            }
+            let p = |i: usize| parts[i];
+            let groups = [
+                [p(0), p(1), p(3), p(4)],
+                [p(1), p(2), p(4), p(5)],
+                [p(3), p(4), p(6), p(7)],
+                [p(4), p(5), p(7), p(8)],
+            ];
+
+            let mut quadrants = [DEAD; 4];
+            for (i, [nw, ne, sw, se]) in groups.into_iter().enumerate() {
+                quadrants[i] = if j < level - 2 {
+                    // 진행시킨 시간이 2^j로 충분하면 각 부분의 중앙만 모은다
+                    self.join([
+                        self.children(nw)[3],
+                        self.children(ne)[2],
+                        self.children(sw)[1],
+                        self.children(se)[0],
+                    ])
+                } else {
+                    // 그렇지 않으면 모은 노드를 다시 한 번 진행시킨다
+                    let group = self.join([nw, ne, sw, se]);
+                    self.successor(group, j, rule)
+                };
+            }
+            self.join(quadrants)
+        };
+
+        self.results.insert((node, j), result);
+        result
+    }
+
+    // 루트에서 닿는 노드만 남기고 나머지를 버린다
+    fn collect_garbage(&mut self) {
+        let old_nodes = std::mem::take(&mut self.nodes);
+        self.nodes = old_nodes[..=ALIVE as usize].to_vec();
+        self.lookup.clear();
+        self.results.clear();
+        self.empty = vec![DEAD];
+
+        let mut copied = HashMap::new();
+        self.root = self.copy_node(&old_nodes, self.root, &mut copied);
+    }
+
+    fn copy_node(
+        &mut self,
+        old_nodes: &[Node],
+        node: NodeId,
+        copied: &mut HashMap<NodeId, NodeId>,
+    ) -> NodeId {
+        if node <= ALIVE {
+            return node;
+        }
+        if let Some(new_node) = copied.get(&node) {
+            return *new_node;
+        }
+        let mut children = old_nodes[node as usize].children;
+        for child in children.iter_mut() {
+            *child = self.copy_node(old_nodes, *child, copied);
+        }
+        let new_node = self.join(children);
+        copied.insert(node, new_node);
+        new_node
+    }
+
+    // 2^j 세대만큼 진행한다
+    fn step_pow2(&mut self, j: u8, rule: &Rule) {
+        // 규칙이 바뀌었다면 메모한 결과는 쓸 수 없다
+        if self.results_rule != Some(*rule) {
+            self.results.clear();
+            self.results_rule = Some(*rule);
+        }
+        if self.nodes.len() > MAX_NODES {
+            self.collect_garbage();
+        }
+
+        // 살아 있는 셀이 중앙의 1/4 안에 들어가고 2^j 세대 후에도 결과 안에 남도록 넓힌다
+        loop {
+            let level = self.level(self.root);
+            let inner = self.centre(self.root);
+            let inner = self.centre(inner);
+            if level >= j + 3 && self.population_of(inner) == self.population_of(self.root) {
+                break;
+            }
+            self.expand();
+        }
+
+        let level = self.level(self.root);
+        self.root = self.successor(self.root, j, rule);
+        let quarter = 1_i64 << (level - 2);
+        self.origin = (self.origin.0 + quarter, self.origin.1 + quarter);
+    }
+
+    fn set_node(&mut self, node: NodeId, x: i64, y: i64, alive: bool) -> NodeId {
+        let level = self.level(node);
+        if level == 0 {
+            return if alive { ALIVE } else { DEAD };
+        }
+        let half = 1_i64 << (level - 1);
+        let i = (y >= half) as usize * 2 + (x >= half) as usize;
+        let mut children = self.children(node);
+        children[i] = self.set_node(children[i], x % half, y % half, alive);
+        self.join(children)
+    }
+
+    fn collect_live_cells(&self, node: NodeId, x: i64, y: i64, cells: &mut Vec<(i64, i64)>) {
+        if self.population_of(node) == 0 {
+            return;
+        }
+        let level = self.level(node);
+        if level == 0 {
+            cells.push((x, y));
+            return;
+        }
+        let half = 1_i64 << (level - 1);
+        for (i, child) in self.children(node).into_iter().enumerate() {
+            let dx = if i % 2 == 1 { half } else { 0 };
+            let dy = if i / 2 == 1 { half } else { 0 };
+            self.collect_live_cells(child, x + dx, y + dy, cells);
+        }
+    }
+
+    fn contains(&self, x: i64, y: i64) -> bool {
+        let size = 1_i64 << self.level(self.root);
+        (self.origin.0..self.origin.0 + size).contains(&x)
+            && (self.origin.1..self.origin.1 + size).contains(&y)
+    }
+}
+
+impl Engine for HashLife {
+    fn name(&self) -> String {
+        format!("hashlife nodes {}", self.nodes.len())
+    }
+
+    fn step(&mut self, rule: &Rule) {
+        self.step_pow2(0, rule);
+    }
+
+    // 세대 수를 2의 거듭제곱으로 나누어 각각 한 번에 진행한다
+    fn advance(&mut self, rule: &Rule, generations: u64) {
+        for j in 0..u64::BITS as u8 {
+            if generations >> j & 1 != 0 {
+                self.step_pow2(j, rule);
+            }
+        }
+    }
+
+    fn get(&self, x: i64, y: i64) -> bool {
+        if !self.contains(x, y) {
+            return false;
+        }
+        let (mut x, mut y) = (x - self.origin.0, y - self.origin.1);
+        let mut node = self.root;
+        while self.level(node) > 0 {
+            let half = 1_i64 << (self.level(node) - 1);
+            node = self.children(node)[(y >= half) as usize * 2 + (x >= half) as usize];
+            x %= half;
+            y %= half;
+        }
+        node == ALIVE
+    }
+
+    fn set(&mut self, x: i64, y: i64, alive: bool) {
+        while !self.contains(x, y) {
+            self.expand();
+        }
+        self.root = self.set_node(self.root, x - self.origin.0, y - self.origin.1, alive);
+    }
+
+    fn size(&self) -> Option<(usize, usize)> {
+        None
+    }
+
+    fn live_cells(&self) -> Vec<(i64, i64)> {
+        let mut cells = Vec::new();
+        self.collect_live_cells(self.root, self.origin.0, self.origin.1, &mut cells);
+        cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::{Field, Topology};
+    use crate::pattern::Pattern;
+
+    // 가장자리에 닿지 않는 넓은 grid 엔진과 hashlife 엔진에 같은 패턴을 놓는다
+    fn engines(name: &str) -> (Field, HashLife) {
+        let pattern = Pattern::named(name).unwrap();
+        let mut field = Field::new(112, 112, Topology::Plane);
+        let mut hashlife = HashLife::new();
+        pattern.transfer(&mut field, 40, 40);
+        pattern.transfer(&mut hashlife, 40, 40);
+        (field, hashlife)
+    }
+
+    fn sorted(mut cells: Vec<(i64, i64)>) -> Vec<(i64, i64)> {
+        cells.sort_unstable();
+        cells
+    }
+
+    #[test]
+    fn matches_grid_step_by_step() {
+        let rule = Rule::default();
+        let (mut field, mut hashlife) = engines("r-pentomino");
+        for generation in 1..=64 {
+            field.step(&rule);
+            hashlife.step(&rule);
+            assert_eq!(
+                sorted(hashlife.live_cells()),
+                sorted(field.live_cells()),
+                "gen {generation}"
+            );
+        }
+    }
+
+    #[test]
+    fn matches_grid_after_jump() {
+        for (name, rule, generations) in [
+            ("r-pentomino", "B3/S23", 100),
+            ("gosper-gun", "B3/S23", 64),
+            ("acorn", "B36/S23", 90),
+        ] {
+            let rule = Rule::parse(rule).unwrap();
+            let (mut field, mut hashlife) = engines(name);
+            field.advance(&rule, generations);
+            hashlife.advance(&rule, generations);
+            assert_eq!(
+                sorted(hashlife.live_cells()),
+                sorted(field.live_cells()),
+                "{name} {rule} gen {generations}"
+            );
+        }
+    }
+
+    #[test]
+    fn glider_survives_a_huge_jump() {
+        // 글라이더는 4세대마다 대각선으로 한 칸 움직인다
+        let rule = Rule::default();
+        let mut hashlife = HashLife::new();
+        Pattern::named("glider")
+            .unwrap()
+            .transfer(&mut hashlife, 0, 0);
+        let start = sorted(hashlife.live_cells());
+        hashlife.advance(&rule, 4);
+        let moved = sorted(hashlife.live_cells());
+        let (dx, dy) = (moved[0].0 - start[0].0, moved[0].1 - start[0].1);
+
+        let generations: u64 = 1 << 40;
+        hashlife.advance(&rule, generations - 4);
+        let distance = (generations / 4) as i64;
+        let expected: Vec<_> = start
+            .iter()
+            .map(|(x, y)| (x + dx * distance, y + dy * distance))
+            .collect();
+        assert_eq!(sorted(hashlife.live_cells()), expected);
+    }
+}
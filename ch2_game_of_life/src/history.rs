@@ -0,0 +1,165 @@
+use std::collections::VecDeque;
+
+use crate::engine::Engine;
+
+// 한 세대의 필드를 살아 있는 셀과 죽어 가는 셀의 좌표로만 기록한 것
+pub struct Snapshot {
+    pub generation: u64,
+    live_cells: Vec<(i64, i64)>,
+    dying_cells: Vec<(i64, i64, u8)>,
+}
+
+impl Snapshot {
+    pub fn capture(generation: u64, engine: &dyn Engine) -> Self {
+        Self {
+            generation,
+            live_cells: engine.live_cells(),
+            dying_cells: engine.dying_cells(),
+        }
+    }
+
+    // 엔진의 필드를 이 기록의 상태로 되돌린다
+    pub fn restore(&self, engine: &mut dyn Engine) {
+        for (x, y) in engine.live_cells() {
+            engine.set(x, y, false);
+        }
+        for (x, y, _) in engine.dying_cells() {
+            engine.set_state(x, y, 0);
+        }
+        for (x, y) in &self.live_cells {
+            engine.set(*x, *y, true);
+        }
+        for (x, y, state) in &self.dying_cells {
+            engine.set_state(*x, *y, *state);
+        }
+    }
+}
+
+// 지나간 세대를 capacity개까지 기억하는 링 버퍼
+// position은 지금 표시하고 있는 세대의 위치로, 되감은 뒤에 편집하면 그보다 뒤의 기록은 버린다
+pub struct History {
+    snapshots: VecDeque<Snapshot>,
+    capacity: usize,
+    position: usize,
+}
+
+impl History {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            snapshots: VecDeque::new(),
+            capacity: capacity.max(1),
+            position: 0,
+        }
+    }
+
+    // 지금의 위치 뒤에 새로운 세대를 기록한다. 그보다 뒤의 기록은 버리고 거기서부터 갈라진다
+    pub fn record(&mut self, snapshot: Snapshot) {
+        self.snapshots.truncate(self.position + 1);
+        self.snapshots.push_back(snapshot);
+        if self.snapshots.len() > self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.position = self.snapshots.len() - 1;
+    }
+
+    // count세대분 되감는다. 더 이상 되감을 수 없으면 None을 반환한다
+    pub fn back(&mut self, count: usize) -> Option<&Snapshot> {
+        if self.position == 0 {
+            return None;
+        }
+        self.position = self.position.saturating_sub(count);
+        self.snapshots.get(self.position)
+    }
+
+    // 되감은 상태라면 기록을 count세대분 앞으로 나아간다. 기록의 끝이면 None을 반환한다
+    pub fn forward(&mut self, count: usize) -> Option<&Snapshot> {
+        if self.position + 1 >= self.snapshots.len() {
+            return None;
+        }
+        self.position = (self.position + count).min(self.snapshots.len() - 1);
+        self.snapshots.get(self.position)
+    }
+
+    // 상태 행에 표시하는 「위치/기록의 수」
+    pub fn summary(&self) -> String {
+        format!("history {}/{}", self.position + 1, self.snapshots.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::{Field, Topology};
+    use crate::rule::Rule;
+
+    // generation세대째라는 것만 알 수 있는 빈 필드의 기록
+    fn snapshot(generation: u64) -> Snapshot {
+        Snapshot::capture(generation, &Field::new(4, 4, Topology::Torus))
+    }
+
+    fn generation(snapshot: Option<&Snapshot>) -> Option<u64> {
+        snapshot.map(|snapshot| snapshot.generation)
+    }
+
+    #[test]
+    fn oldest_generations_are_evicted_at_capacity() {
+        let mut history = History::new(3);
+        for i in 0..5 {
+            history.record(snapshot(i));
+        }
+        assert_eq!(history.summary(), "history 3/3");
+        assert_eq!(generation(history.back(1)), Some(3));
+        assert_eq!(generation(history.back(1)), Some(2));
+        // 0세대와 1세대는 이미 버려졌다
+        assert_eq!(generation(history.back(1)), None);
+    }
+
+    #[test]
+    fn back_and_forward_stop_at_the_ends() {
+        let mut history = History::new(10);
+        for i in 0..5 {
+            history.record(snapshot(i));
+        }
+        assert_eq!(generation(history.forward(1)), None);
+        assert_eq!(generation(history.back(3)), Some(1));
+        assert_eq!(generation(history.back(10)), Some(0));
+        assert_eq!(generation(history.back(1)), None);
+        assert_eq!(history.summary(), "history 1/5");
+        assert_eq!(generation(history.forward(2)), Some(2));
+        assert_eq!(generation(history.forward(10)), Some(4));
+        assert_eq!(generation(history.forward(1)), None);
+    }
+
+    #[test]
+    fn record_after_back_drops_the_forward_branch() {
+        let mut history = History::new(10);
+        for i in 0..5 {
+            history.record(snapshot(i));
+        }
+        history.back(2);
+        history.record(snapshot(100));
+        assert_eq!(history.summary(), "history 4/4");
+        assert_eq!(generation(history.forward(1)), None);
+        assert_eq!(generation(history.back(1)), Some(2));
+        assert_eq!(generation(history.forward(1)), Some(100));
+    }
+
+    #[test]
+    fn snapshot_restores_dying_states() {
+        let rule = Rule::parse("B2/S/C4").unwrap();
+        let mut field = Field::new(8, 8, Topology::Torus);
+        field.set(3, 3, true);
+        field.set(4, 3, true);
+        field.step(&rule);
+        field.step(&rule);
+        let saved = Snapshot::capture(2, &field);
+        let cells = field.cells.clone();
+        assert!(cells.iter().any(|state| *state >= 2));
+
+        field.step(&rule);
+        field.set_state(0, 0, 3);
+        assert_ne!(field.cells, cells);
+        saved.restore(&mut field);
+        assert_eq!(field.cells, cells);
+    }
+}
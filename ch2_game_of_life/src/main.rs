@@ -1,154 +1,941 @@
 use std::process::exit;
-use std::time::SystemTime;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 
-use getch_rs::Getch;
+use getch_rs::{Getch, Key};
+use rand::Rng;
+use terminal_size::{Height, Width, terminal_size};
+
+mod animation;
+mod bitboard;
+mod engine;
+mod field;
+mod hashlife;
+mod history;
+mod neighbourhood;
+mod pattern;
+mod render;
+mod rule;
+mod soup;
+mod sparse;
+mod stats;
+
+use animation::{AnimationOptions, DEFAULT_PALETTE, Palette};
+use bitboard::BitField;
+use engine::{Engine, EngineKind};
+use field::{Field, Topology};
+use hashlife::HashLife;
+use history::{History, Snapshot};
+use neighbourhood::Neighbourhood;
+use pattern::{LIBRARY, Pattern};
+use render::{RenderMode, Renderer};
+use rule::{PRESETS, Rule};
+use soup::{Census, SOUP_SIZE};
+use sparse::SparseField;
+use stats::Stats;
 
 // [2]상수를 정의하는 곳
-const FIELD_WIDTH: usize = 160;
-const FIELD_HEIGHT: usize = 160;
-const FPS: usize = 10;
-const INTERVAL: f32 = 1000.0 / FPS as f32; // 밀리 초 
+const DEFAULT_FIELD_WIDTH: usize = 160;
+const DEFAULT_FIELD_HEIGHT: usize = 160;
+const FPS: u32 = 10; // 처음의 속도
+const MAX_FPS: u32 = 60;
+const STATUS_LINES: usize = 4; // 필드 아래에 표시하는 행의 수
+const DEFAULT_HISTORY: usize = 1000; // 기억해 두는 세대 수
+const SCRUB_GENERATIONS: usize = 10; // 한 번에 되감거나 빨리 감는 기록의 수
+const SOUP_DENSITY: f64 = 0.35; // 랜덤으로 채울 때 살아 있는 셀의 비율의 기본값
+// 명령줄에서 지정할 수 있는 세대 수의 최대값(2^40)
+// hashlife는 진행하는 세대 수에 맞춰 트리를 넓히므로, 이보다 크면 i64의 좌표가 넘칠 수 있다
+const MAX_GENERATIONS: u64 = 1 << 40;
 
 struct Context {
-    field: Vec<bool>, //bool[FieldHeight][FieldWidth];
-    g: Getch,
+    engine: Box<dyn Engine>,
+    rule: Rule,
+    generation: u64,                // 현재의 세대 수
+    step: u64,                      // 한 프레임에 진행하는 세대 수
+    view: (i64, i64, usize, usize), // 화면에 표시하는 범위(x, y, 폭, 높이)
+    follow: bool,                   // 표시 범위를 살아 있는 셀에 맞추어 움직일지 여부
+    cursor: (i64, i64),             // 편집하는 셀의 좌표
+    paused: bool,
+    fps: u32,         // 1초에 진행하는 프레임의 수
+    soup: (f64, u64), // 랜덤으로 채울 때의 밀도와 다음에 쓰는 seed
+    renderer: Renderer,
+    stats: Stats,
+    history: History,
+    stamp: (usize, u16, bool), // 찍을 내장 패턴의 번호, 회전 각도, 좌우 반전
+    keys: Receiver<Key>,
     last_clock: SystemTime,
+    message: String, // 필드 아래에 표시하는 메시지
 }
 
 impl Context {
-    pub fn new() -> Self {
-        // [3-1]필드를 선언한다
-        // let mut field = vec![false; FIELD_HEIGHT * FIELD_WIDTH];
-        // field[0 * FIELD_WIDTH + 1] = true;
-        // field[1 * FIELD_WIDTH + 2] = true;
-        // field[2 * FIELD_WIDTH + 0] = true;
-        // field[2 * FIELD_WIDTH + 1] = true;
-        // field[2 * FIELD_WIDTH + 2] = true;
+    pub fn new(
+        engine: Box<dyn Engine>,
+        rule: Rule,
+        step: u64,
+        view: (i64, i64, usize, usize),
+        follow: bool,
+        render_mode: RenderMode,
+        history: usize,
+    ) -> Self {
+        // 시뮬레이션을 멈추지 않도록 키 입력은 별도의 스레드에서 기다린다
+        let (sender, keys) = mpsc::channel();
+        thread::spawn(move || {
+            let g = Getch::new();
+            while let Ok(key) = g.getch() {
+                if sender.send(key).is_err() {
+                    break;
+                }
+            }
+        });
+
         Self {
-            field: vec![false; FIELD_HEIGHT * FIELD_WIDTH],
-            g: Getch::new(),
+            engine,
+            rule,
+            generation: 0,
+            step,
+            view,
+            follow,
+            cursor: (view.0 + view.2 as i64 / 2, view.1 + view.3 as i64 / 2),
+            paused: false,
+            fps: FPS,
+            soup: (SOUP_DENSITY, 0),
+            renderer: Renderer::new(render_mode),
+            stats: Stats::new(),
+            history: History::new(history),
+            stamp: (0, 0, false),
+            keys,
             last_clock: SystemTime::now(),
+            message: String::new(),
         }
     }
-    // [4-1]필드를 그리는 함수를 선언한다
-    pub fn draw_field(&self) {
-        clearscreen::clear().unwrap();
-
-        for y in 0..FIELD_HEIGHT {
-            for x in 0..FIELD_WIDTH {
-                // [4-1-4]셀이 살아 있으면「■」를, 죽어 있으면「  」를 그립니다
-                print!(
-                    "{}",
-                    if self.field[y * FIELD_WIDTH + x] {
-                        "■"
-                    } else {
-                        "□"
-                    }
-                );
-            }
-            println!();
-        }
-    }
-    // [4-2]대상 셀과 인접하는 살아 있는 셀의 수를 가져오는 함수를 선언한다
-    pub fn get_living_cells_count(&self, x_: i64, y_: i64) -> u64 {
-        let mut count = 0;
-        for y in y_ - 1..=y_ + 1 {
-            // [4-2-3]상하로 루프시키지 않는 경우는 행이 범위 내인지 여부를 판정한다
-            // if y < 0 || y >= FieldHeight as i64 {
-            //     continue;
-            // }
-            // [4-2-5]상하로 루프한 Y좌표를 선언한다
-            let rooped_y = (FIELD_HEIGHT as i64 + y) % FIELD_HEIGHT as i64;
-            for x in x_ - 1..=x_ + 1 {
-                // [4-2-7]좌우로 루프시키지 않는 경우는 열이 범위 내인지 여부를 판정한다
-                // if x < 0 || x >= FieldWidth as i64 {
-                //     continue;
-                // }
-                // [4-2-9]좌우로 루프한 X좌표를 선언한다
-                let rooped_x = (FIELD_WIDTH as i64 + x) % FIELD_WIDTH as i64;
-                // [4-2-10]대상 좌표가 중심 셀과 같은지 여부를 판정한다
-                if rooped_x == x_ && rooped_y == y_ {
-                    continue;
-                }
-                // [4-2-12]대상 셀이 살아 있으면 1을, 죽어 있으면 0을 가산한다
-                if self.field[rooped_y as usize * FIELD_WIDTH + rooped_x as usize] {
-                    count += 1;
-                }
-            }
+    // 표시 범위의 크기를 터미널에 맞춘다. 크기가 바뀌면 중심을 유지한다
+    pub fn fit_view(&mut self) {
+        let (columns, rows) = terminal_size()
+            .map(|(Width(w), Height(h))| (w as usize, h as usize))
+            .unwrap_or((80, 24));
+        let (mut width, mut height) = self.renderer.capacity(columns, rows, STATUS_LINES);
+        if let Some((field_width, field_height)) = self.engine.size() {
+            width = width.min(field_width);
+            height = height.min(field_height);
         }
-        count // [4-2-13]살아 있는 셀의 수를 반환한다
+        let (x, y, old_width, old_height) = self.view;
+        if (width, height) != (old_width, old_height) {
+            self.view = (
+                x + old_width as i64 / 2 - width as i64 / 2,
+                y + old_height as i64 / 2 - height as i64 / 2,
+                width,
+                height,
+            );
+        }
+    }
+    // [4-1]필드를 그리는 함수를 선언한다
+    pub fn draw_field(&mut self) {
+        self.renderer
+            .set_hexagonal(self.rule.neighbourhood() == Neighbourhood::Hexagonal);
+        self.fit_view();
+
+        // 현재의 세대와 규칙을 표시한다
+        let rule_name = match self.rule.name() {
+            Some(name) => format!("{} ({})", self.rule, name),
+            None => self.rule.to_string(),
+        };
+        let status = [
+            format!(
+                "gen {}  {}  {}fps{}  {}  cursor ({}, {})",
+                self.generation,
+                rule_name,
+                self.fps,
+                if self.paused { "  일시 정지" } else { "" },
+                self.history.summary(),
+                self.cursor.0,
+                self.cursor.1
+            ),
+            self.stats.summary(),
+            format!(
+                "{}  view ({}, {}){}  stamp {} {}°{}",
+                self.engine.name(),
+                self.view.0,
+                self.view.1,
+                if self.follow { " follow" } else { "" },
+                LIBRARY[self.stamp.0].0,
+                self.stamp.1,
+                if self.stamp.2 { " mirror" } else { "" }
+            ),
+            self.message.clone(),
+        ];
+        self.renderer
+            .draw(self.engine.as_ref(), self.view, self.cursor, &status);
     }
     // [4-3]1스텝만큼의 시뮬레이션을 실행하는 함수를 선언한다
+    // 되감은 상태라면 다시 계산하지 않고 기록해 둔 다음 세대를 꺼낸다
     pub fn step_simulation(&mut self) {
-        // [4-3-1]다음 세대의 필드를 선언한다
-        let mut next_field = vec![false; FIELD_HEIGHT * FIELD_WIDTH];
-        for y in 0..FIELD_HEIGHT as i64 {
-            for x in 0..FIELD_WIDTH as i64 {
-                // [4-3-4]대상 셀과 인접하는 살아 있는 셀의 수를 선언한다
-                let living_cell_count = self.get_living_cells_count(x, y);
-                // [4-3-5]인접하는 살아 있는 셀의 수로 분기한다
-                next_field[y as usize * FIELD_WIDTH + x as usize] = match living_cell_count {
-                    0 | 1 => false,
-                    2 => self.field[y as usize * FIELD_WIDTH + x as usize],
-                    3 => true,
-                    _ => false,
-                };
+        if let Some(snapshot) = self.history.forward(1) {
+            snapshot.restore(self.engine.as_mut());
+            self.generation = snapshot.generation;
+        } else {
+            self.engine.advance(&self.rule, self.step);
+
+            self.generation += self.step;
+            self.history
+                .record(Snapshot::capture(self.generation, self.engine.as_ref()));
+        }
+        self.stats.record(self.generation, self.engine.as_ref());
+    }
+    // 기록을 count세대분 되감거나(back이 true) 빨리 감는다
+    pub fn scrub(&mut self, count: usize, back: bool) {
+        let snapshot = if back {
+            self.history.back(count)
+        } else {
+            self.history.forward(count)
+        };
+        let Some(snapshot) = snapshot else {
+            self.message = "더 이상 기록이 없습니다".to_string();
+            return;
+        };
+        snapshot.restore(self.engine.as_mut());
+        self.generation = snapshot.generation;
+        self.paused = true;
+
+        // 세대를 건너뛰었으므로 주기 검출은 여기서부터 다시 시작한다
+        self.stats.reset();
+        self.stats.record(self.generation, self.engine.as_ref());
+    }
+    // 편집으로 세대의 이력이 끊겼으므로 통계와 주기 검출을 다시 시작하고
+    // 지금의 필드를 기록한다. 되감은 상태였다면 여기서부터 기록이 갈라진다
+    pub fn edited(&mut self) {
+        self.stats.reset();
+        self.stats.record(self.generation, self.engine.as_ref());
+        self.history
+            .record(Snapshot::capture(self.generation, self.engine.as_ref()));
+    }
+    // 1프레임의 대기 시간(밀리 초)
+    pub fn interval(&self) -> f32 {
+        1000.0 / self.fps as f32
+    }
+    // 속도를 1fps씩 바꾼다
+    pub fn change_speed(&mut self, delta: i32) {
+        self.fps = self.fps.saturating_add_signed(delta).clamp(1, MAX_FPS);
+    }
+    // 커서를 움직인다. 표시 범위 밖으로 나가면 표시 범위도 따라 움직인다
+    pub fn move_cursor(&mut self, dx: i64, dy: i64) {
+        self.cursor = (self.cursor.0 + dx, self.cursor.1 + dy);
+        let (x, y, width, height) = self.view;
+        let (width, height) = (width as i64, height as i64);
+        let view_x = x.clamp(self.cursor.0 - width + 1, self.cursor.0);
+        let view_y = y.clamp(self.cursor.1 - height + 1, self.cursor.1);
+        if (view_x, view_y) != (x, y) {
+            self.view = (view_x, view_y, width as usize, height as usize);
+            self.follow = false;
+        }
+    }
+    // 커서가 있는 셀의 삶과 죽음을 뒤집는다
+    pub fn toggle_cell(&mut self) {
+        let (x, y) = self.cursor;
+        let alive = self.engine.get(x, y);
+        self.engine.set(x, y, !alive);
+        self.edited();
+    }
+    // 선택한 내장 패턴의 왼쪽 위를 커서에 맞추어 찍는다
+    pub fn stamp_pattern(&mut self) {
+        let (index, rotation, mirror) = self.stamp;
+        let pattern = Pattern::named(LIBRARY[index].0).unwrap();
+        pattern.stamp(
+            self.engine.as_mut(),
+            self.cursor.0,
+            self.cursor.1,
+            rotation,
+            mirror,
+        );
+        self.edited();
+    }
+    // 모든 셀을 죽이고 세대를 처음으로 되돌린다
+    pub fn clear_field(&mut self) {
+        for (x, y) in self.engine.live_cells() {
+            self.engine.set(x, y, false);
+        }
+        for (x, y, _) in self.engine.dying_cells() {
+            self.engine.set_state(x, y, 0);
+        }
+        self.generation = 0;
+        self.edited();
+    }
+    // 표시 범위를 랜덤인 셀로 채운다
+    // 같은 seed로 다시 채울 수 있도록 쓴 seed를 표시하고 다음에는 그다음 seed를 쓴다
+    pub fn fill_random(&mut self) {
+        let (view_x, view_y, width, height) = self.view;
+        let (density, seed) = self.soup;
+        // 패턴은 살아 있는 셀만 찍으므로 표시 범위를 먼저 비운다
+        for y in view_y..view_y + height as i64 {
+            for x in view_x..view_x + width as i64 {
+                self.engine.set_state(x, y, 0);
             }
-            println!();
-        }
-        // [4-3-13]다음 스텝의 필드를 현재 필드에 복사한다
-        self.field.clone_from_slice(&next_field);
-    }
-    // [4-4]패턴을 필드에 복사하는 함수를 선언한다
-    pub fn pattern_transfer(
-        &mut self,
-        dest_x: usize,
-        dest_y: usize,
-        src_width: usize,
-        src_height: usize,
-        p_pattern: &[bool],
-    ) {
-        for y in 0..src_height {
-            for x in 0..src_width {
-                // [4-4-3]패턴을 필드에 복사한다
-                self.field[(dest_y + y) * FIELD_WIDTH + dest_x + x] = p_pattern[y * src_width + x];
+        }
+        soup::generate(width, height, density, seed).transfer(self.engine.as_mut(), view_x, view_y);
+        self.message = format!("seed {seed}, 밀도 {density}로 채웠습니다");
+        self.soup.1 = seed.wrapping_add(1);
+        self.generation = 0;
+        self.edited();
+    }
+
+    // 표시 범위를 폭과 높이의 1/4씩 dx, dy 방향으로 움직인다
+    pub fn pan(&mut self, dx: i64, dy: i64) {
+        let (x, y, width, height) = self.view;
+        let step_x = (width as i64 / 4).max(1);
+        let step_y = (height as i64 / 4).max(1);
+        self.view = (x + dx * step_x, y + dy * step_y, width, height);
+        self.follow = false;
+    }
+    // 살아 있는 셀의 바운딩 박스의 중심이 표시 범위의 중심에 오도록 한다
+    pub fn follow_live_cells(&mut self) {
+        let Some((x, y, live_width, live_height)) = self.stats.bounding_box else {
+            return;
+        };
+        let (_, _, width, height) = self.view;
+        self.view = (
+            x + live_width as i64 / 2 - width as i64 / 2,
+            y + live_height as i64 / 2 - height as i64 / 2,
+            width,
+            height,
+        );
+    }
+    // 필드 전체(끝이 없는 엔진과 trim이 true일 때는 셀이 있는 범위)를
+    // 「접두사_gen세대.rle」와「접두사_gen세대.cells」에 써낸다
+    pub fn export_field(&mut self, prefix: &str, trim: bool) {
+        let mut pattern = Pattern::from_engine(self.engine.as_ref(), trim);
+        pattern.rule = Some(self.rule);
+
+        let comments = [format!("Generation: {}", self.generation)];
+        let paths = [
+            format!("{prefix}_gen{}.rle", self.generation),
+            format!("{prefix}_gen{}.cells", self.generation),
+        ];
+        self.message = match paths
+            .iter()
+            .try_for_each(|path| pattern.save(path, &comments))
+        {
+            Ok(()) => format!("{}, {}에 저장했습니다", paths[0], paths[1]),
+            Err(e) => e,
+        };
+    }
+}
+
+const USAGE: &str = "사용법: ch2_game_of_life [--rule B3/S23] [--size 160x160] [--topology torus|plane|klein|cylinder]
+       [--engine grid|bitboard|hashlife|sparse] [--threads N] [--step 세대 수|2^k] [--bench 세대 수]
+       [--headless 최대 세대 수] [--offset X,Y] [--follow] [--render cell|half|braille]
+       [--history 세대 수] [--export 접두사] [--animate 세대 수] [--output life.gif|접두사]
+       [--every K] [--cell-size 픽셀 수] [--palette ffffff,000000,...]
+       [--rotate 0|90|180|270] [--mirror] [--soup 폭x높이] [--density 0~1] [--seed N]
+       [--search 수프의 수] [패턴 파일(.rle/.cells) 또는 내장 패턴의 이름]
+  내장 패턴: glider, lwss, gosper-gun, pulsar, acorn, r-pentomino
+  규칙: B3/S23, Generations는 B2/S/C3, 폰 노이만 근방은 B3/S23V, 육각형 격자는 B2/S34H,
+        Larger than Life는 R5,C0,M1,S34..58,B34..45,NM (N은 M: 무어, N: 폰 노이만). 근방이 다르면 grid 엔진만 쓸 수 있다
+  표시 범위는 터미널의 크기에 맞춘다. hashlife, sparse 엔진은 끝이 없는 평면이고 --size는 처음의 중심을 정한다
+  --step으로 한 프레임에 여러 세대를 진행한다. --headless는 주기를 검출하면 멈추고 요약을 출력한다
+  세대 수는 모두 2^40까지 지정할 수 있다
+  --soup는 패턴 대신 seed에서 만든 랜덤 수프로 시작한다. 같은 seed와 밀도면 같은 수프가 된다
+  --search는 seed부터 차례로 16x16 수프를 진행시켜 안정된 뒤에 남은 고정물, 진동자, 우주선을 센다
+  --animate는 표시하지 않고 K세대마다의 프레임을 GIF 애니메이션(.gif) 또는 PNG 연번 파일(접두사_세대.png)로 저장한다
+키: 방향키 커서 이동, x/Enter 셀 반전, Space 일시 정지/재개, n 1스텝, b 1세대 되돌리기,
+    </> 10세대 되감기/빨리 감기(되감은 곳에서 편집하면 기록이 갈라진다), +/- 속도 변경,
+    c 모두 지우기, s 랜덤으로 채우기(--density, --seed), r 규칙 변경, p 살아 있는 셀의 범위를 저장, f 필드 전체를 저장,
+    l 찍을 내장 패턴 선택, o 90도 회전, m 좌우 반전, v 커서 위치에 찍기,
+    H/J/K/L 표시 범위 이동, a 살아 있는 셀을 따라가기 전환, q/ESC 종료";
+
+// 명령줄 인수로 지정하는 설정
+struct Options {
+    rule: Option<Rule>,           // 생략하면 패턴 파일의 규칙, 그것도 없으면 B3/S23
+    pattern_path: Option<String>, // 생략하면 내장 패턴
+    width: usize,
+    height: usize,
+    topology: Topology,
+    engine: EngineKind,
+    threads: usize,                    // bitboard 엔진이 사용하는 스레드의 수
+    step: u64,                         // 한 프레임(벤치마크에서는 한 번)에 진행하는 세대 수
+    bench_generations: Option<u64>,    // 지정하면 표시하지 않고 엔진의 속도를 비교한다
+    headless_generations: Option<u64>, // 지정하면 표시하지 않고 주기가 나타날 때까지 진행한다
+    offset: (i64, i64),                // 필드 중앙으로부터 패턴을 어긋나게 하는 양
+    follow: bool,                      // 표시 범위가 살아 있는 셀을 따라가게 한다
+    render_mode: RenderMode,
+    rotation: u16,                    // 처음의 패턴을 시계 방향으로 돌리는 각도
+    mirror: bool,                     // 처음의 패턴의 좌우를 뒤집을지 여부
+    history: usize,                   // 되감기 위해 기억해 두는 세대 수
+    animate_generations: Option<u64>, // 지정하면 표시하지 않고 GIF 또는 PNG로 저장한다
+    animation_path: String,           // .gif면 GIF 애니메이션, 그 밖에는 PNG 파일의 접두사
+    every: u64,                       // 몇 세대마다 한 프레임을 저장할지
+    cell_size: usize,                 // 셀 하나의 픽셀 수
+    palette: Palette,
+    export_prefix: String,        // 저장하는 파일 이름의 접두사
+    density: f64,                 // 랜덤으로 채울 때 살아 있는 셀의 비율
+    seed: u64,                    // 처음에 쓰는 난수의 seed. 생략하면 랜덤
+    soup: Option<(usize, usize)>, // 지정하면 패턴 대신 이 크기의 랜덤 수프로 시작한다
+    search_soups: Option<u64>,    // 지정하면 이 개수의 수프를 검색하여 물체를 센다
+}
+
+impl Options {
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Self {
+            rule: None,
+            pattern_path: None,
+            width: DEFAULT_FIELD_WIDTH,
+            height: DEFAULT_FIELD_HEIGHT,
+            topology: Topology::Torus,
+            engine: EngineKind::Grid,
+            threads: 1,
+            step: 1,
+            bench_generations: None,
+            headless_generations: None,
+            offset: (0, 0),
+            follow: false,
+            render_mode: RenderMode::HalfBlock,
+            rotation: 0,
+            mirror: false,
+            history: DEFAULT_HISTORY,
+            animate_generations: None,
+            animation_path: "life.gif".to_string(),
+            every: 1,
+            cell_size: 4,
+            palette: Palette::parse(DEFAULT_PALETTE).unwrap(),
+            export_prefix: "life".to_string(),
+            density: SOUP_DENSITY,
+            seed: rand::rng().random(),
+            soup: None,
+            search_soups: None,
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--rule" => {
+                    let value = args.next().ok_or("--rule에 규칙 문자열이 필요합니다")?;
+                    options.rule = Some(Rule::parse(value)?);
+                }
+                "--size" => {
+                    let value = args.next().ok_or("--size에 폭x높이가 필요합니다")?;
+                    (options.width, options.height) = parse_size(value)
+                        .ok_or_else(|| format!("--size의 값이 잘못되었습니다: {value}"))?;
+                }
+                "--topology" => {
+                    let value = args.next().ok_or("--topology에 위상이 필요합니다")?;
+                    options.topology = Topology::parse(value)?;
+                }
+                "--engine" => {
+                    let value = args.next().ok_or("--engine에 엔진의 이름이 필요합니다")?;
+                    options.engine = EngineKind::parse(value)?;
+                }
+                "--threads" => {
+                    let value = args.next().ok_or("--threads에 스레드의 수가 필요합니다")?;
+                    options.threads = value
+                        .parse()
+                        .ok()
+                        .filter(|n| *n > 0)
+                        .ok_or_else(|| format!("--threads의 값이 잘못되었습니다: {value}"))?;
+                }
+                "--step" => {
+                    let value = args.next().ok_or("--step에 세대 수가 필요합니다")?;
+                    options.step = parse_generations(value)
+                        .filter(|n| *n > 0)
+                        .ok_or_else(|| format!("--step의 값이 잘못되었습니다: {value}"))?;
+                }
+                "--bench" => {
+                    let value = args.next().ok_or("--bench에 세대 수가 필요합니다")?;
+                    options.bench_generations = Some(
+                        parse_generations(value)
+                            .ok_or_else(|| format!("--bench의 값이 잘못되었습니다: {value}"))?,
+                    );
+                }
+                "--headless" => {
+                    let value = args
+                        .next()
+                        .ok_or("--headless에 최대 세대 수가 필요합니다")?;
+                    options.headless_generations = Some(
+                        parse_generations(value)
+                            .ok_or_else(|| format!("--headless의 값이 잘못되었습니다: {value}"))?,
+                    );
+                }
+                "--offset" => {
+                    let value = args.next().ok_or("--offset에 X,Y가 필요합니다")?;
+                    options.offset = value
+                        .split_once(',')
+                        .and_then(|(x, y)| Some((x.trim().parse().ok()?, y.trim().parse().ok()?)))
+                        .ok_or_else(|| format!("--offset의 값이 잘못되었습니다: {value}"))?;
+                }
+                "--follow" => options.follow = true,
+                "--history" => {
+                    let value = args.next().ok_or("--history에 세대 수가 필요합니다")?;
+                    options.history = value
+                        .parse()
+                        .ok()
+                        .filter(|n| *n > 0)
+                        .ok_or_else(|| format!("--history의 값이 잘못되었습니다: {value}"))?;
+                }
+                "--animate" => {
+                    let value = args.next().ok_or("--animate에 세대 수가 필요합니다")?;
+                    options.animate_generations = Some(
+                        parse_generations(value)
+                            .ok_or_else(|| format!("--animate의 값이 잘못되었습니다: {value}"))?,
+                    );
+                }
+                "--output" => {
+                    let value = args.next().ok_or("--output에 파일 이름이 필요합니다")?;
+                    options.animation_path = value.clone();
+                }
+                "--every" => {
+                    let value = args.next().ok_or("--every에 세대 수가 필요합니다")?;
+                    options.every = parse_generations(value)
+                        .filter(|n| *n > 0)
+                        .ok_or_else(|| format!("--every의 값이 잘못되었습니다: {value}"))?;
+                }
+                "--cell-size" => {
+                    let value = args.next().ok_or("--cell-size에 픽셀 수가 필요합니다")?;
+                    options.cell_size = value
+                        .parse()
+                        .ok()
+                        .filter(|n| *n > 0)
+                        .ok_or_else(|| format!("--cell-size의 값이 잘못되었습니다: {value}"))?;
+                }
+                "--palette" => {
+                    let value = args.next().ok_or("--palette에 색이 필요합니다")?;
+                    options.palette = Palette::parse(value)?;
+                }
+                "--rotate" => {
+                    let value = args.next().ok_or("--rotate에 각도가 필요합니다")?;
+                    options.rotation = value
+                        .parse()
+                        .ok()
+                        .filter(|angle| [0, 90, 180, 270].contains(angle))
+                        .ok_or_else(|| {
+                            format!("--rotate는 0, 90, 180, 270 중 하나여야 합니다: {value}")
+                        })?;
+                }
+                "--mirror" => options.mirror = true,
+                "--render" => {
+                    let value = args.next().ok_or("--render에 표시 방법이 필요합니다")?;
+                    options.render_mode = RenderMode::parse(value)?;
+                }
+                "--export" => {
+                    let value = args
+                        .next()
+                        .ok_or("--export에 파일 이름의 접두사가 필요합니다")?;
+                    options.export_prefix = value.clone();
+                }
+                "--density" => {
+                    let value = args.next().ok_or("--density에 0~1의 비율이 필요합니다")?;
+                    options.density = value
+                        .parse()
+                        .ok()
+                        .filter(|d| (0.0..=1.0).contains(d))
+                        .ok_or_else(|| format!("--density는 0~1이어야 합니다: {value}"))?;
+                }
+                "--seed" => {
+                    let value = args.next().ok_or("--seed에 정수가 필요합니다")?;
+                    options.seed = value
+                        .parse()
+                        .map_err(|_| format!("--seed의 값이 잘못되었습니다: {value}"))?;
+                }
+                "--soup" => {
+                    let value = args.next().ok_or("--soup에 폭x높이가 필요합니다")?;
+                    options.soup = Some(
+                        parse_size(value)
+                            .ok_or_else(|| format!("--soup의 값이 잘못되었습니다: {value}"))?,
+                    );
+                }
+                "--search" => {
+                    let value = args.next().ok_or("--search에 수프의 수가 필요합니다")?;
+                    options.search_soups =
+                        Some(
+                            value.parse().ok().filter(|n| *n > 0).ok_or_else(|| {
+                                format!("--search의 값이 잘못되었습니다: {value}")
+                            })?,
+                        );
+                }
+                path if !path.starts_with("--") && options.pattern_path.is_none() => {
+                    options.pattern_path = Some(path.to_string());
+                }
+                arg => return Err(format!("알 수 없는 인수: {arg}")),
             }
         }
+        Ok(options)
     }
 }
 
-fn main() {
-    let mut ctx = Context::new();
+// 「160x160」같은 폭x높이를 해석한다
+fn parse_size(value: &str) -> Option<(usize, usize)> {
+    value
+        .split_once('x')
+        .and_then(|(w, h)| Some((w.trim().parse().ok()?, h.trim().parse().ok()?)))
+        .filter(|(w, h)| *w > 0 && *h > 0)
+}
+
+// 「1000」이나「2^20」같은 세대 수를 해석한다. MAX_GENERATIONS보다 크면 None을 반환한다
+fn parse_generations(value: &str) -> Option<u64> {
+    match value.split_once('^') {
+        Some(("2", exponent)) => 1_u64.checked_shl(exponent.trim().parse().ok()?),
+        Some(_) => None,
+        None => value.parse().ok(),
+    }
+    .filter(|generations| *generations <= MAX_GENERATIONS)
+}
 
+// 패턴 파일이 지정되지 않았을 때 사용하는 내장 패턴
+fn builtin_pattern() -> Pattern {
     const PATTERN_WIDTH: usize = 10;
     const PATTERN_HEIGHT: usize = 8;
 
-    // [4-5-3]패턴을 선언한다
-    let pattern = vec![
-        false, false, false, false, false, false, false, false, false, false, false, false, false,
-        false, false, false, false, true, false, false, false, false, false, false, false, true,
-        false, true, true, false, false, false, false, false, false, true, false, true, false,
-        false, false, false, false, false, false, true, false, false, false, false, false, false,
-        false, true, false, false, false, false, false, false, false, true, false, true, false,
-        false, false, false, false, false, false, false, false, false, false, false, false, false,
-        false, false,
+    Pattern {
+        width: PATTERN_WIDTH,
+        height: PATTERN_HEIGHT,
+        cells: [
+            false, false, false, false, false, false, false, false, false, false, false, false,
+            false, false, false, false, false, true, false, false, false, false, false, false,
+            false, true, false, true, true, false, false, false, false, false, false, true, false,
+            true, false, false, false, false, false, false, false, true, false, false, false,
+            false, false, false, false, true, false, false, false, false, false, false, false,
+            true, false, true, false, false, false, false, false, false, false, false, false,
+            false, false, false, false, false, false, false,
+        ]
+        .map(u8::from)
+        .to_vec(),
+        rule: None,
+    }
+}
+
+// 옵션에 따라 엔진을 만든다
+fn create_engine(options: &Options, kind: EngineKind, threads: usize) -> Box<dyn Engine> {
+    match kind {
+        EngineKind::Grid => Box::new(Field::new(options.width, options.height, options.topology)),
+        EngineKind::Bitboard => Box::new(BitField::new(
+            options.width,
+            options.height,
+            options.topology,
+            threads,
+        )),
+        EngineKind::HashLife => Box::new(HashLife::new()),
+        EngineKind::Sparse => Box::new(SparseField::new()),
+    }
+}
+
+// 같은 패턴을 각 엔진으로 진행시켜 걸린 시간을 비교하고 결과가 일치하는지 확인한다
+fn run_benchmark(
+    options: &Options,
+    pattern: &Pattern,
+    dest: (i64, i64),
+    rule: &Rule,
+    generations: u64,
+) {
+    // 끝이 없는 평면은 유한한 필드와는 결과를 비교할 수 없으므로 단독으로 잰다
+    if options.engine.is_unbounded() {
+        let mut engine = create_engine(options, options.engine, 1);
+        pattern.transfer(engine.as_mut(), dest.0, dest.1);
+        let start = Instant::now();
+        engine.advance(rule, generations);
+        let elapsed = start.elapsed().as_secs_f64();
+        println!("{:<32} {:>10.3}초", engine.name(), elapsed);
+        println!(
+            "{}세대 후의 살아 있는 셀 {}개",
+            generations,
+            engine.live_cells().len()
+        );
+        return;
+    }
+
+    let mut engines = vec![
+        create_engine(options, EngineKind::Grid, 1),
+        create_engine(options, EngineKind::Bitboard, 1),
     ];
+    if options.threads > 1 {
+        engines.push(create_engine(
+            options,
+            EngineKind::Bitboard,
+            options.threads,
+        ));
+    }
+
+    let mut results = Vec::new();
+    for engine in engines.iter_mut() {
+        pattern.transfer(engine.as_mut(), dest.0, dest.1);
+        let start = Instant::now();
+        for _ in 0..generations {
+            engine.step(rule);
+        }
+        let elapsed = start.elapsed().as_secs_f64();
+        println!(
+            "{:<32} {:>10.3}초 {:>12.1}세대/초",
+            engine.name(),
+            elapsed,
+            generations as f64 / elapsed
+        );
+        results.push(engine.live_cells());
+    }
+
+    if results.windows(2).all(|pair| pair[0] == pair[1]) {
+        println!(
+            "{}세대 후의 결과가 모두 일치합니다 (살아 있는 셀 {}개)",
+            generations,
+            results[0].len()
+        );
+    } else {
+        println!("{}세대 후의 결과가 엔진마다 다릅니다", generations);
+        exit(1);
+    }
+}
+
+// 표시하지 않고 진행시켜 주기가 나타나거나 최대 세대 수에 이르면 요약을 출력한다
+fn run_headless(
+    options: &Options,
+    pattern: &Pattern,
+    dest: (i64, i64),
+    rule: &Rule,
+    max_generations: u64,
+) {
+    let mut engine = create_engine(options, options.engine, options.threads);
+    pattern.transfer(engine.as_mut(), dest.0, dest.1);
+
+    // --step이 1보다 크면 그 간격으로만 비교하므로 주기도 step의 배수로 검출된다
+    let mut stats = Stats::new();
+    let mut generation = 0;
+    stats.record(generation, engine.as_ref());
+    while stats.cycle.is_none() && generation < max_generations {
+        let step = options.step.min(max_generations - generation);
+        engine.advance(rule, step);
+        generation += step;
+        stats.record(generation, engine.as_ref());
+    }
+
+    println!("{}  {}", engine.name(), rule);
+    if let Some((width, height)) = options.soup {
+        println!(
+            "{width}x{height} 수프  seed {}  밀도 {}",
+            options.seed, options.density
+        );
+    }
+    println!("gen {generation}  {}", stats.summary());
+    match stats.cycle {
+        Some(cycle) => println!(
+            "gen {}부터 주기 {}의 반복을 gen {}에 검출했습니다",
+            cycle.start, cycle.period, generation
+        ),
+        None => println!("{max_generations}세대까지 주기는 나타나지 않았습니다"),
+    }
+}
+
+// 수프를 seed부터 차례로 soups개 진행시켜 안정된 뒤에 남은 물체를 종류마다 세어 출력한다
+fn run_search(options: &Options, rule: &Rule, soups: u64) {
+    println!(
+        "{rule}  {SOUP_SIZE}x{SOUP_SIZE} 수프  seed {}~{}  밀도 {}",
+        options.seed,
+        options.seed.wrapping_add(soups - 1),
+        options.density
+    );
+    let start = Instant::now();
+    let census = Census::search(rule, soups, options.seed, options.density);
+    println!("{:.3}초", start.elapsed().as_secs_f64());
+    print!("{}", census.report());
+}
+
+// 표시하지 않고 진행시키며 필드(끝이 없는 엔진에서는 --size의 범위)를 GIF 또는 PNG로 저장한다
+fn run_animation(
+    options: &Options,
+    pattern: &Pattern,
+    dest: (i64, i64),
+    rule: &Rule,
+    generations: u64,
+) {
+    let mut engine = create_engine(options, options.engine, options.threads);
+    pattern.transfer(engine.as_mut(), dest.0, dest.1);
+
+    let animation = AnimationOptions {
+        path: options.animation_path.clone(),
+        generations,
+        every: options.every,
+        cell_size: options.cell_size,
+        palette: options.palette.clone(),
+    };
+    let region = (0, 0, options.width, options.height);
+    match animation::export_animation(engine.as_mut(), rule, region, &animation) {
+        Ok(frames) => println!("{}에 {}프레임을 저장했습니다", animation.path, frames),
+        Err(e) => {
+            eprintln!("{e}");
+            exit(1);
+        }
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let options = Options::parse(&args).unwrap_or_else(|e| {
+        eprintln!("{e}");
+        eprintln!("{USAGE}");
+        exit(1);
+    });
+
+    // [4-5-3]패턴을 선언한다. 내장 패턴의 이름이면 라이브러리에서, 그 밖에는 파일에서 읽는다
+    // --soup를 지정하면 seed에서 만든 랜덤 수프를 쓴다
+    let pattern = match (&options.pattern_path, options.soup) {
+        (_, Some((width, height))) => soup::generate(width, height, options.density, options.seed),
+        (Some(path), None) => Pattern::named(path)
+            .map_or_else(|| Pattern::load(path), Ok)
+            .unwrap_or_else(|e| {
+                eprintln!("{e}");
+                exit(1);
+            }),
+        (None, None) => builtin_pattern(),
+    }
+    .transformed(options.rotation, options.mirror);
+
+    // 규칙은 명령줄, 패턴 파일, 기본값 순으로 정한다
+    let rule = options.rule.or(pattern.rule).unwrap_or_default();
+
+    // 수프 검색은 끝이 없는 평면의 sparse 엔진으로 진행한다
+    if let Some(soups) = options.search_soups {
+        if !EngineKind::Sparse.supports(&rule) || rule.next_state(false, 0) {
+            eprintln!(
+                "{rule}: 수프 검색은 B0을 포함하지 않는 무어 근방(8셀)의 2상태 규칙에서만 사용할 수 있습니다"
+            );
+            exit(1);
+        }
+        run_search(&options, &rule, soups);
+        return;
+    }
+
+    // 끝이 없는 평면에서 B0 규칙은 무한히 많은 셀을 탄생시키므로 다룰 수 없다
+    let unbounded = options.engine.is_unbounded();
+    if unbounded && rule.next_state(false, 0) {
+        eprintln!("{rule}: B0을 포함하는 규칙은 끝이 없는 평면의 엔진에서 사용할 수 없습니다");
+        exit(1);
+    }
+
+    // 죽어 가는 상태와 무어 근방(8셀) 이외의 근방은 grid 엔진만 다룰 수 있다
+    // 벤치마크는 bitboard 엔진과 비교하므로 쓸 수 없다
+    if !options.engine.supports(&rule)
+        || (options.bench_generations.is_some() && !EngineKind::Bitboard.supports(&rule))
+    {
+        eprintln!(
+            "{rule}: Generations 규칙과 무어 근방(8셀) 이외의 규칙은 grid 엔진의 표시와 --headless, --animate에서만 사용할 수 있습니다"
+        );
+        exit(1);
+    }
+
+    // [4-5-4]패턴을 필드의 중심(에서 오프셋만큼 어긋난 곳)에 복사한다
+    let dest_x = (options.width as i64 - pattern.width as i64) / 2 + options.offset.0;
+    let dest_y = (options.height as i64 - pattern.height as i64) / 2 + options.offset.1;
+    if !unbounded
+        && (dest_x < 0
+            || dest_y < 0
+            || dest_x as usize + pattern.width > options.width
+            || dest_y as usize + pattern.height > options.height)
+    {
+        eprintln!(
+            "{}x{} 패턴이 {}x{} 필드에 들어가지 않습니다",
+            pattern.width, pattern.height, options.width, options.height
+        );
+        exit(1);
+    }
+
+    if let Some(generations) = options.bench_generations {
+        run_benchmark(&options, &pattern, (dest_x, dest_y), &rule, generations);
+        return;
+    }
+
+    if let Some(generations) = options.animate_generations {
+        run_animation(&options, &pattern, (dest_x, dest_y), &rule, generations);
+        return;
+    }
 
-    // [4-5-4]패턴을 필드의 중심에 복사한다
-    ctx.pattern_transfer(
-        FIELD_WIDTH / 2 - PATTERN_WIDTH / 2,
-        FIELD_HEIGHT / 2 - PATTERN_HEIGHT / 2,
-        PATTERN_WIDTH,
-        PATTERN_HEIGHT,
-        &pattern,
+    if let Some(generations) = options.headless_generations {
+        run_headless(&options, &pattern, (dest_x, dest_y), &rule, generations);
+        return;
+    }
+
+    // 표시 범위는 필드의 중심에서 시작하여 처음 그릴 때 터미널의 크기에 맞춘다
+    let mut ctx = Context::new(
+        create_engine(&options, options.engine, options.threads),
+        rule,
+        options.step,
+        (options.width as i64 / 2, options.height as i64 / 2, 0, 0),
+        options.follow,
+        options.render_mode,
+        options.history,
     );
 
+    pattern.transfer(ctx.engine.as_mut(), dest_x, dest_y);
+    ctx.soup = (options.density, options.seed);
+    if options.soup.is_some() {
+        ctx.soup.1 = options.seed.wrapping_add(1);
+        ctx.message = format!(
+            "seed {}, 밀도 {}의 수프로 시작했습니다",
+            options.seed, options.density
+        );
+    }
+    ctx.edited();
+
+    // 처음의 필드를 그린다
+    let mut redraw = true;
     loop {
+        // [4-5-12]눌린 키를 처리한다. 키가 눌리지 않았으면 기다리지 않고 지나간다
+        while let Ok(key) = ctx.keys.try_recv() {
+            redraw = true;
+            ctx.message.clear();
+            match key {
+                Key::Esc | Key::Char('q') => {
+                    ctx.renderer.finish();
+                    println!("gen {}에서 종료했습니다", ctx.generation);
+                    return;
+                }
+                Key::Char(' ') => ctx.paused = !ctx.paused,
+                Key::Char('n') => {
+                    // 일시 정지하고 1스텝만 진행한다
+                    ctx.paused = true;
+                    ctx.step_simulation();
+                }
+                Key::Char('b') => ctx.scrub(1, true),
+                Key::Char('<') | Key::Char(',') => ctx.scrub(SCRUB_GENERATIONS, true),
+                Key::Char('>') | Key::Char('.') => ctx.scrub(SCRUB_GENERATIONS, false),
+                Key::Char('+') | Key::Char('=') => ctx.change_speed(1),
+                Key::Char('-') => ctx.change_speed(-1),
+                Key::Up => ctx.move_cursor(0, -1),
+                Key::Down => ctx.move_cursor(0, 1),
+                Key::Left => ctx.move_cursor(-1, 0),
+                Key::Right => ctx.move_cursor(1, 0),
+                Key::Char('x') | Key::Char('\n') | Key::Char('\r') => ctx.toggle_cell(),
+                Key::Char('c') => ctx.clear_field(),
+                Key::Char('l') => ctx.stamp.0 = (ctx.stamp.0 + 1) % LIBRARY.len(),
+                Key::Char('o') => ctx.stamp.1 = (ctx.stamp.1 + 90) % 360,
+                Key::Char('m') => ctx.stamp.2 = !ctx.stamp.2,
+                Key::Char('v') => ctx.stamp_pattern(),
+                Key::Char('s') => ctx.fill_random(),
+                Key::Char('r') => {
+                    // 다음 프리셋 규칙으로 바꾼다. 엔진이 다룰 수 없는 규칙은 건너뛴다
+                    let current = PRESETS
+                        .iter()
+                        .position(|(name, _)| Some(*name) == ctx.rule.name());
+                    ctx.rule = (1..=PRESETS.len())
+                        .map(|offset| current.map_or(offset - 1, |i| (i + offset) % PRESETS.len()))
+                        .map(|i| Rule::parse(PRESETS[i].1).unwrap())
+                        .find(|rule| options.engine.supports(rule))
+                        .unwrap();
+                    ctx.edited();
+                }
+                Key::Char('p') => ctx.export_field(&options.export_prefix, true),
+                Key::Char('f') => ctx.export_field(&options.export_prefix, false),
+                Key::Char('H') => ctx.pan(-1, 0),
+                Key::Char('J') => ctx.pan(0, 1),
+                Key::Char('K') => ctx.pan(0, -1),
+                Key::Char('L') => ctx.pan(1, 0),
+                Key::Char('a') => ctx.follow = !ctx.follow,
+                _ => {}
+            }
+        }
+
         match ctx.last_clock.elapsed() {
             Ok(elapsed) => {
-                // [4-5-8]이전 회의 경과 시간에서 대기 시간이 경과하지 않으면
-                if (elapsed.as_millis() as f32) < INTERVAL {
-                    continue;
+                // [4-5-8]이전 회의 경과 시간에서 대기 시간이 경과했고 일시 정지 중이 아니면
+                if !ctx.paused && elapsed.as_millis() as f32 >= ctx.interval() {
+                    // [4-5-10]이전 회 경과 시간을 현재의 경과 시간으로 갱신한다
+                    ctx.last_clock = SystemTime::now();
+
+                    // [4-5-13]시뮬레이션을 진행한다
+                    ctx.step_simulation();
+                    redraw = true;
                 }
             }
             Err(e) => {
@@ -158,21 +945,33 @@ fn main() {
             }
         }
 
-        // [4-5-10]이전 회 경과 시간을 현재의 경과 시간으로 갱신한다
-        ctx.last_clock = SystemTime::now();
+        if redraw {
+            if ctx.follow {
+                ctx.follow_live_cells();
+            }
 
-        // [4-5-11]필드를 그리는 함수를 호출한다
-        ctx.draw_field();
+            // [4-5-11]필드를 그리는 함수를 호출한다
+            ctx.draw_field();
+            redraw = false;
+        } else {
+            // 할 일이 없는 동안은 CPU를 계속 쓰지 않도록 잠깐 쉰다
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+}
 
-        // [4-5-12]키보드 입력을 기다린다
-        // match self.g.getch() {
-        //     Ok(Key::Esc) => {
-        //         exit(0);
-        //     }
-        //     _ => {}
-        // }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        // [4-5-13]시뮬레이션을 진행한다
-        ctx.step_simulation();
+    #[test]
+    fn parse_generations_up_to_the_maximum() {
+        assert_eq!(parse_generations("1000"), Some(1000));
+        assert_eq!(parse_generations("2^20"), Some(1 << 20));
+        assert_eq!(parse_generations("2^40"), Some(MAX_GENERATIONS));
+        assert_eq!(parse_generations("1099511627776"), Some(MAX_GENERATIONS));
+        for value in ["2^41", "1099511627777", "2^62", "2^64", "3^2", "-1", "x"] {
+            assert_eq!(parse_generations(value), None, "{value}");
+        }
     }
 }
@@ -0,0 +1,235 @@
+// 이웃으로 세는 셀의 범위
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Neighbourhood {
+    Moore(u8),      // 체비쇼프 거리가 범위 이내인 셀. 범위 1이 보통의 8셀
+    VonNeumann(u8), // 맨해튼 거리가 범위 이내인 셀. 범위 1이면 상하좌우의 4셀
+    Hexagonal,      // 홀수 행을 반 셀 오른쪽으로 어긋나게 놓은 육각형 격자의 6셀
+}
+
+impl Neighbourhood {
+    // 범위로 지정할 수 있는 최댓값. 이웃의 수가 127을 넘지 않도록 한다
+    pub const MAX_MOORE_RANGE: u8 = 5;
+    pub const MAX_VON_NEUMANN_RANGE: u8 = 7;
+
+    // (dx, dy)가 y행의 셀의 이웃인지 여부
+    fn contains(&self, dx: i64, dy: i64, odd_row: bool) -> bool {
+        if (dx, dy) == (0, 0) {
+            return false;
+        }
+        match *self {
+            Neighbourhood::Moore(range) => dx.abs().max(dy.abs()) <= range as i64,
+            Neighbourhood::VonNeumann(range) => dx.abs() + dy.abs() <= range as i64,
+            // 위아래 행에서는 짝수 행이면 왼쪽 두 셀, 홀수 행이면 오른쪽 두 셀이 이웃이다
+            Neighbourhood::Hexagonal => match dy {
+                0 => dx.abs() == 1,
+                -1 | 1 if odd_row => dx == 0 || dx == 1,
+                -1 | 1 => dx == -1 || dx == 0,
+                _ => false,
+            },
+        }
+    }
+
+    fn range(&self) -> i64 {
+        match *self {
+            Neighbourhood::Moore(range) | Neighbourhood::VonNeumann(range) => range as i64,
+            Neighbourhood::Hexagonal => 1,
+        }
+    }
+
+    // 이웃의 상대 좌표를 짝수 행과 홀수 행으로 나누어 반환한다
+    pub fn offsets(&self) -> [Vec<(i64, i64)>; 2] {
+        let range = self.range();
+        [false, true].map(|odd_row| {
+            let mut offsets = Vec::new();
+            for dy in -range..=range {
+                for dx in -range..=range {
+                    if self.contains(dx, dy, odd_row) {
+                        offsets.push((dx, dy));
+                    }
+                }
+            }
+            offsets
+        })
+    }
+
+    // 이웃의 수
+    pub fn size(&self) -> usize {
+        self.offsets()[0].len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Engine;
+    use crate::field::{Field, Topology};
+    use crate::pattern::Pattern;
+    use crate::rule::Rule;
+
+    fn sorted(mut cells: Vec<(i64, i64)>) -> Vec<(i64, i64)> {
+        cells.sort_unstable();
+        cells
+    }
+
+    #[test]
+    fn neighbourhood_sizes() {
+        for (neighbourhood, size) in [
+            (Neighbourhood::Moore(1), 8),
+            (Neighbourhood::Moore(2), 24),
+            (Neighbourhood::Moore(Neighbourhood::MAX_MOORE_RANGE), 120),
+            (Neighbourhood::VonNeumann(1), 4),
+            (Neighbourhood::VonNeumann(2), 12),
+            (
+                Neighbourhood::VonNeumann(Neighbourhood::MAX_VON_NEUMANN_RANGE),
+                112,
+            ),
+            (Neighbourhood::Hexagonal, 6),
+        ] {
+            let [even, odd] = neighbourhood.offsets();
+            assert_eq!(neighbourhood.size(), size, "{neighbourhood:?}");
+            assert_eq!((even.len(), odd.len()), (size, size), "{neighbourhood:?}");
+        }
+    }
+
+    #[test]
+    fn hexagonal_offsets_depend_on_the_row() {
+        let [even, odd] = Neighbourhood::Hexagonal.offsets();
+        assert_eq!(even, [(-1, -1), (0, -1), (-1, 0), (1, 0), (-1, 1), (0, 1)]);
+        assert_eq!(odd, [(0, -1), (1, -1), (-1, 0), (1, 0), (0, 1), (1, 1)]);
+
+        // 짝수 행 2의 (3, 2)는 홀수 행 3에서는 (2, 3)과 (3, 3)의 이웃이고
+        // 홀수 행 3의 (3, 3)은 짝수 행 2와 4에서는 (3, y)와 (4, y)의 이웃이다
+        let offsets = [&even, &odd];
+        for (live, expected) in [
+            (
+                (3, 2),
+                [(2, 3, 1), (3, 3, 1), (4, 3, 0), (2, 1, 1), (4, 1, 0)],
+            ),
+            (
+                (3, 3),
+                [(2, 2, 0), (3, 2, 1), (4, 2, 1), (2, 4, 0), (4, 4, 1)],
+            ),
+        ] {
+            let mut field = Field::new(8, 8, Topology::Plane);
+            field.set(live.0, live.1, true);
+            for (x, y, count) in expected {
+                assert_eq!(
+                    field.get_living_cells_count(x, y, offsets[y as usize % 2]),
+                    count,
+                    "{live:?} ({x}, {y})"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn range_counts_in_a_full_field() {
+        let mut field = Field::new(15, 15, Topology::Plane);
+        for y in 0..15 {
+            for x in 0..15 {
+                field.set(x, y, true);
+            }
+        }
+        for (neighbourhood, centre, corner) in [
+            (Neighbourhood::Moore(2), 24, 8),
+            (Neighbourhood::Moore(5), 120, 35),
+            (Neighbourhood::VonNeumann(3), 24, 9),
+        ] {
+            let offsets = &neighbourhood.offsets()[0];
+            assert_eq!(field.get_living_cells_count(7, 7, offsets), centre);
+            assert_eq!(field.get_living_cells_count(0, 0, offsets), corner);
+        }
+    }
+
+    #[test]
+    fn von_neumann_single_cell_grows_into_a_diamond() {
+        let rule = Rule::parse("B1/SV").unwrap();
+        let mut field = Field::new(9, 9, Topology::Plane);
+        field.set(4, 4, true);
+        field.step(&rule);
+        assert_eq!(sorted(field.live_cells()), [(3, 4), (4, 3), (4, 5), (5, 4)]);
+    }
+
+    #[test]
+    fn larger_than_life_matches_direct_count() {
+        // 범위 안의 셀을 하나하나 세어 구한 다음 세대와 비교한다
+        let rule = Rule::parse("R3,C0,M1,S10..20,B12..16,NM").unwrap();
+        let (width, height) = (24, 20);
+        let mut field = Field::new(width, height, Topology::Torus);
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        for y in 0..height as i64 {
+            for x in 0..width as i64 {
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                field.set(x, y, seed.is_multiple_of(2));
+            }
+        }
+        for generation in 1..=5 {
+            let mut expected = Vec::new();
+            for y in 0..height as i64 {
+                for x in 0..width as i64 {
+                    // M1이므로 중심의 셀도 센다
+                    let mut count = 0;
+                    for dy in -3..=3 {
+                        for dx in -3..=3 {
+                            count += field.get(
+                                (x + dx).rem_euclid(width as i64),
+                                (y + dy).rem_euclid(height as i64),
+                            ) as usize;
+                        }
+                    }
+                    let alive = if field.get(x, y) {
+                        (10..=20).contains(&count)
+                    } else {
+                        (12..=16).contains(&count)
+                    };
+                    if alive {
+                        expected.push((x, y));
+                    }
+                }
+            }
+            field.step(&rule);
+            assert_eq!(
+                sorted(field.live_cells()),
+                sorted(expected),
+                "{generation}세대"
+            );
+        }
+    }
+
+    #[test]
+    fn larger_than_life_spaceship() {
+        // Bosco의 규칙에서 16세대마다 대각선으로 (8, 8)씩 나아가는 우주선
+        let pattern = Pattern::parse_rle(
+            "x = 11, y = 11, rule = R5,C0,M1,S34..58,B34..45,NM\n\
+             3b4o$b2ob5o$bo3b5o$o5b4o$2o5b4o$3o4b4o$4o2b4o$b9o$b8o$2b6o$4b2o!",
+        )
+        .unwrap();
+        let rule = pattern.rule.unwrap();
+        let mut field = Field::new(48, 48, Topology::Torus);
+        let mut expected = Vec::new();
+        for y in 0..pattern.height {
+            for x in 0..pattern.width {
+                if pattern.cells[y * pattern.width + x] != 0 {
+                    field.set(x as i64 + 10, y as i64 + 10, true);
+                    expected.push((x as i64 + 10, y as i64 + 10));
+                }
+            }
+        }
+        for period in 1..=3 {
+            for _ in 0..16 {
+                field.step(&rule);
+            }
+            for (x, y) in expected.iter_mut() {
+                *x += 8;
+                *y += 8;
+            }
+            assert_eq!(
+                sorted(field.live_cells()),
+                sorted(expected.clone()),
+                "{period}주기"
+            );
+        }
+    }
+}
@@ -0,0 +1,661 @@
+use std::fs;
+use std::path::Path;
+
+use crate::engine::Engine;
+use crate::rule::Rule;
+use crate::stats::bounding_box;
+
+// 내장 패턴 라이브러리(이름, RLE)
+pub const LIBRARY: [(&str, &str); 6] = [
+    ("glider", "x = 3, y = 3\nbo$2bo$3o!"),
+    ("lwss", "x = 5, y = 4\nbo2bo$o4b$o3bo$4o!"),
+    (
+        "gosper-gun",
+        "x = 36, y = 9\n24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$\
+         2o8bo3bob2o4bobo$10bo5bo7bo$11bo3bo$12b2o!",
+    ),
+    (
+        "pulsar",
+        "x = 13, y = 13\n2b3o3b3o2$o4bobo4bo$o4bobo4bo$o4bobo4bo$2b3o3b3o2$2b3o3b3o$\
+         o4bobo4bo$o4bobo4bo$o4bobo4bo2$2b3o3b3o!",
+    ),
+    ("acorn", "x = 7, y = 3\nbo$3bo$2o2b3o!"),
+    ("r-pentomino", "x = 3, y = 3\nb2o$2o$bo!"),
+];
+
+// 필드에 복사할 수 있는 직사각형 패턴
+#[derive(Clone)]
+pub struct Pattern {
+    pub width: usize,
+    pub height: usize,
+    pub cells: Vec<u8>, // u8[height][width], 셀의 상태(0: 죽은 셀, 1: 살아 있는 셀, 2 이상: 죽어 가는 셀)
+    pub rule: Option<Rule>, // 파일에 규칙이 적혀 있으면 그 규칙
+}
+
+impl Pattern {
+    // 죽은 셀이 아닌 셀의 좌표와 상태의 목록으로 패턴을 만든다
+    fn from_coordinates(width: usize, height: usize, coordinates: &[(usize, usize, u8)]) -> Self {
+        let width = coordinates
+            .iter()
+            .map(|(x, _, _)| x + 1)
+            .fold(width, usize::max);
+        let height = coordinates
+            .iter()
+            .map(|(_, y, _)| y + 1)
+            .fold(height, usize::max);
+        let mut cells = vec![0; width * height];
+        for (x, y, state) in coordinates {
+            cells[y * width + x] = *state;
+        }
+        Self {
+            width,
+            height,
+            cells,
+            rule: None,
+        }
+    }
+
+    // 엔진의 셀을 잘라 내어 패턴을 만든다. 유한한 필드라면 필드 전체를,
+    // 끝이 없는 우주이거나 trim이 true면 죽어 가는 셀을 포함한 모든 셀의 바운딩 박스를 잘라 낸다
+    pub fn from_engine(engine: &dyn Engine, trim: bool) -> Self {
+        let cells: Vec<(i64, i64, u8)> = engine
+            .live_cells()
+            .into_iter()
+            .map(|(x, y)| (x, y, 1))
+            .chain(engine.dying_cells())
+            .collect();
+        let (x0, y0, width, height) = match engine.size() {
+            Some((width, height)) if !trim => (0, 0, width, height),
+            _ => {
+                let positions: Vec<_> = cells.iter().map(|(x, y, _)| (*x, *y)).collect();
+                bounding_box(&positions).unwrap_or((0, 0, 0, 0))
+            }
+        };
+        let coordinates: Vec<_> = cells
+            .iter()
+            .map(|(x, y, state)| ((x - x0) as usize, (y - y0) as usize, *state))
+            .collect();
+        Self::from_coordinates(width, height, &coordinates)
+    }
+
+    // 내장 패턴 라이브러리에서 이름으로 패턴을 꺼낸다
+    // 대소문자는 구별하지 않으며 공백과 '_'는 '-'와 같이 다룬다(예:「Gosper gun」)
+    pub fn named(name: &str) -> Option<Self> {
+        let name = name.trim().to_ascii_lowercase().replace([' ', '_'], "-");
+        LIBRARY
+            .iter()
+            .find(|(library_name, _)| *library_name == name)
+            .map(|(_, rle)| Self::parse_rle(rle).unwrap())
+    }
+
+    // 시계 방향으로 rotation도(0, 90, 180, 270) 돌린 패턴을 반환한다
+    // mirror가 true면 돌리기 전에 좌우를 뒤집는다
+    pub fn transformed(&self, rotation: u16, mirror: bool) -> Self {
+        let mut pattern = self.clone();
+        if mirror {
+            for row in pattern.cells.chunks_mut(pattern.width.max(1)) {
+                row.reverse();
+            }
+        }
+        for _ in 0..rotation / 90 % 4 {
+            // 90도 돌리면 (x, y)는 (높이 - 1 - y, x)로 옮겨진다
+            let mut cells = vec![0; pattern.cells.len()];
+            for y in 0..pattern.height {
+                for x in 0..pattern.width {
+                    cells[x * pattern.height + (pattern.height - 1 - y)] =
+                        pattern.cells[y * pattern.width + x];
+                }
+            }
+            pattern = Self {
+                width: pattern.height,
+                height: pattern.width,
+                cells,
+                rule: pattern.rule,
+            };
+        }
+        pattern
+    }
+
+    // 돌리거나 뒤집은 패턴의 왼쪽 위를 (dest_x, dest_y)에 맞추어 필드에 찍는다
+    // 가장자리를 넘은 부분은 엔진의 위상에 따라 반대쪽으로 돌아가거나 잘린다
+    // 패턴의 죽은 셀은 찍지 않으므로 그 자리에 있던 셀은 그대로 남는다
+    pub fn stamp(
+        &self,
+        engine: &mut dyn Engine,
+        dest_x: i64,
+        dest_y: i64,
+        rotation: u16,
+        mirror: bool,
+    ) {
+        self.transformed(rotation, mirror)
+            .transfer(engine, dest_x, dest_y);
+    }
+
+    // [4-4]패턴을 필드에 복사하는 함수를 선언한다
+    // 좌표는 엔진의 set_state()가 위상에 따라 다루므로 필드 밖에 걸쳐도 된다
+    // 죽은 셀은 복사하지 않으므로 직사각형 안의 원래 셀을 지우지 않는다
+    pub fn transfer(&self, engine: &mut dyn Engine, dest_x: i64, dest_y: i64) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let state = self.cells[y * self.width + x];
+                if state == 0 {
+                    continue;
+                }
+                // [4-4-3]패턴을 필드에 복사한다
+                engine.set_state(dest_x + x as i64, dest_y + y as i64, state);
+            }
+        }
+    }
+
+    // 파일을 읽어 들인다. 확장자가 .cells면 플레인 텍스트, 그 밖에는 RLE로 해석한다
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text =
+            fs::read_to_string(path).map_err(|e| format!("{path}을(를) 읽을 수 없습니다: {e}"))?;
+        let is_plaintext = Path::new(path)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("cells"));
+        if is_plaintext {
+            Self::parse_plaintext(&text)
+        } else {
+            Self::parse_rle(&text)
+        }
+        .map_err(|e| format!("{path}: {e}"))
+    }
+
+    // RLE 형식을 해석한다
+    //   #N 이름, #C 코멘트 등 '#'로 시작하는 행은 무시한다
+    //   「x = 3, y = 3, rule = B3/S23」헤더 뒤에 「<개수><태그>」의 나열이 이어진다
+    //   태그는 b(죽은 셀), o(살아 있는 셀), $(행의 끝), !(패턴의 끝)
+    //   Generations 규칙의 패턴은 .(죽은 셀), A(상태 1), B(상태 2), ..., X(상태 24)를 쓰고
+    //   상태 25 이상은 pA~pX, qA~qX, ..., yA~yO처럼 앞에 p~y를 붙인다
+    pub fn parse_rle(text: &str) -> Result<Self, String> {
+        let mut lines = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+
+        // 헤더를 해석한다
+        let header = lines.next().ok_or("RLE 헤더(x = .., y = ..)가 없습니다")?;
+        let mut width = None;
+        let mut height = None;
+        let mut rule = None;
+        // Larger than Life의 규칙에는 쉼표가 들어가므로 rule은 헤더의 끝까지를 값으로 한다
+        let (header_items, rule_item) = match header.find("rule") {
+            Some(i) => (&header[..i], Some(&header[i..])),
+            None => (header, None),
+        };
+        for item in header_items
+            .split(',')
+            .filter(|item| !item.trim().is_empty())
+            .chain(rule_item)
+        {
+            let (key, value) = item
+                .split_once('=')
+                .ok_or_else(|| format!("RLE 헤더를 해석할 수 없습니다: \"{header}\""))?;
+            let value = value.trim();
+            match key.trim() {
+                "x" => width = value.parse().ok(),
+                "y" => height = value.parse().ok(),
+                "rule" => rule = Some(Rule::parse(value)?),
+                _ => {}
+            }
+        }
+        let (Some(width), Some(height)) = (width, height) else {
+            return Err(format!("RLE 헤더에 x와 y가 필요합니다: \"{header}\""));
+        };
+
+        // 셀의 나열을 해석한다
+        let mut coordinates = Vec::new();
+        let (mut x, mut y) = (0, 0);
+        let mut count = 0;
+        let mut prefix = None; // 상태 25 이상을 나타내는 p~y
+        'data: for line in lines {
+            for c in line.chars() {
+                if let Some(p) = prefix
+                    && !c.is_ascii_uppercase()
+                {
+                    return Err(format!(
+                        "RLE 데이터의 '{p}' 뒤에 상태를 나타내는 대문자가 필요합니다"
+                    ));
+                }
+                let state = match c {
+                    '0'..='9' => {
+                        count = count * 10 + c.to_digit(10).unwrap() as usize;
+                        continue;
+                    }
+                    'b' | '.' => 0,
+                    '$' => {
+                        y += count.max(1);
+                        x = 0;
+                        count = 0;
+                        continue;
+                    }
+                    '!' => break 'data,
+                    'p'..='y' => {
+                        prefix = Some(c);
+                        continue;
+                    }
+                    'A'..='X' => {
+                        let high = prefix.take().map_or(0, |p| p as usize - 'p' as usize + 1);
+                        u8::try_from(high * 24 + (c as usize - 'A' as usize) + 1)
+                            .map_err(|_| format!("RLE 데이터의 상태가 255를 넘습니다: {c}"))?
+                    }
+                    c if c.is_ascii_alphabetic() => 1,
+                    c if c.is_whitespace() => continue,
+                    c => return Err(format!("RLE 데이터에 알 수 없는 문자 '{c}'이(가) 있습니다")),
+                };
+                let count = std::mem::take(&mut count).max(1);
+                if state != 0 {
+                    coordinates.extend((x..x + count).map(|x| (x, y, state)));
+                }
+                x += count;
+            }
+        }
+
+        let mut pattern = Self::from_coordinates(width, height, &coordinates);
+        pattern.rule = rule;
+        Ok(pattern)
+    }
+
+    // 플레인 텍스트(.cells) 형식을 해석한다
+    //   '!'로 시작하는 행은 코멘트, '.'은 죽은 셀, 'O'는 살아 있는 셀
+    //   to_plaintext가 쓰는 「!Rule: B3/S23」코멘트가 있으면 그 규칙을 읽는다
+    pub fn parse_plaintext(text: &str) -> Result<Self, String> {
+        let mut coordinates = Vec::new();
+        let mut width = 0;
+        let mut height = 0;
+        let mut rule = None;
+        for line in text.lines() {
+            if let Some(comment) = line.strip_prefix('!') {
+                if let Some(value) = comment.strip_prefix("Rule:") {
+                    rule = Some(Rule::parse(value)?);
+                }
+                continue;
+            }
+            let line = line.trim_end();
+            for (x, c) in line.chars().enumerate() {
+                match c {
+                    'O' | 'o' | '*' => coordinates.push((x, height, 1)),
+                    '.' | ' ' => {}
+                    c => {
+                        return Err(format!(
+                            "{}행에 알 수 없는 문자 '{c}'이(가) 있습니다",
+                            height + 1
+                        ));
+                    }
+                }
+            }
+            width = width.max(line.chars().count());
+            height += 1;
+        }
+        let mut pattern = Self::from_coordinates(width, height, &coordinates);
+        pattern.rule = rule;
+        Ok(pattern)
+    }
+
+    // 파일에 써낸다. 확장자가 .cells면 플레인 텍스트, 그 밖에는 RLE로 쓴다
+    pub fn save(&self, path: &str, comments: &[String]) -> Result<(), String> {
+        let is_plaintext = Path::new(path)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("cells"));
+        let text = if is_plaintext {
+            self.to_plaintext(comments)
+        } else {
+            self.to_rle(comments)
+        };
+        fs::write(path, text).map_err(|e| format!("{path}에 쓸 수 없습니다: {e}"))
+    }
+
+    // RLE 형식의 문자열로 변환한다. 코멘트는 「#C」행으로 쓴다
+    // 규칙이 Generations 규칙이면 죽어 가는 상태도 남도록 여러 상태의 태그(., A, B, ...)로 쓴다
+    pub fn to_rle(&self, comments: &[String]) -> String {
+        const LINE_LENGTH: usize = 70;
+
+        let mut text = String::new();
+        for comment in comments {
+            text += &format!("#C {comment}\n");
+        }
+        text += &format!("x = {}, y = {}", self.width, self.height);
+        if let Some(rule) = self.rule {
+            text += &format!(", rule = {rule}");
+        }
+        text += "\n";
+
+        // 「<개수><태그>」의 나열을 만든다. 행 끝의 죽은 셀은 생략하고 빈 행은 「<개수>$」로 합친다
+        let multi_state = self.rule.is_some_and(|rule| rule.states() > 2);
+        let mut items = Vec::new();
+        let mut pending_rows = 0;
+        for row in self.cells.chunks(self.width.max(1)) {
+            // 2상태의 규칙이면 죽어 가는 셀은 죽은 셀로 쓴다
+            let row: Vec<u8> = if multi_state {
+                row.to_vec()
+            } else {
+                row.iter().map(|state| (*state == 1) as u8).collect()
+            };
+            let length = row
+                .iter()
+                .rposition(|state| *state != 0)
+                .map_or(0, |x| x + 1);
+            if length > 0 {
+                if pending_rows > 0 {
+                    items.push(run_length(pending_rows, "$"));
+                    pending_rows = 0;
+                }
+                let mut x = 0;
+                while x < length {
+                    let count = row[x..length].iter().take_while(|c| **c == row[x]).count();
+                    let tag = match (multi_state, row[x]) {
+                        (true, state) => multi_state_tag(state),
+                        (false, 1) => "o".to_string(),
+                        (false, _) => "b".to_string(),
+                    };
+                    items.push(run_length(count, &tag));
+                    x += count;
+                }
+            }
+            pending_rows += 1;
+        }
+        items.push("!".to_string());
+
+        // 한 행이 LINE_LENGTH자를 넘지 않도록 줄바꿈한다
+        let mut line = String::new();
+        for item in items {
+            if line.len() + item.len() > LINE_LENGTH {
+                text += &line;
+                text += "\n";
+                line.clear();
+            }
+            line += &item;
+        }
+        text += &line;
+        text += "\n";
+        text
+    }
+
+    // 플레인 텍스트(.cells) 형식의 문자열로 변환한다. 코멘트는 「!」행으로 쓴다
+    // 이 형식에는 죽어 가는 상태가 없으므로 살아 있는 셀만 남는다
+    pub fn to_plaintext(&self, comments: &[String]) -> String {
+        let mut text = String::new();
+        if let Some(rule) = self.rule {
+            text += &format!("!Rule: {rule}\n");
+        }
+        for comment in comments {
+            text += &format!("!{comment}\n");
+        }
+        for row in self.cells.chunks(self.width.max(1)) {
+            for state in row {
+                text.push(if *state == 1 { 'O' } else { '.' });
+            }
+            text += "\n";
+        }
+        text
+    }
+}
+
+// 개수가 1이면 생략하여 「<개수><태그>」를 만든다
+fn run_length(count: usize, tag: &str) -> String {
+    if count == 1 {
+        tag.to_string()
+    } else {
+        format!("{count}{tag}")
+    }
+}
+
+// 여러 상태의 RLE에서 상태를 나타내는 태그. 0은 「.」, 1~24는 「A」~「X」, 25 이상은 「pA」부터
+fn multi_state_tag(state: u8) -> String {
+    if state == 0 {
+        return ".".to_string();
+    }
+    let n = state as usize - 1;
+    let letter = (b'A' + (n % 24) as u8) as char;
+    match n / 24 {
+        0 => letter.to_string(),
+        high => format!("{}{letter}", (b'p' + high as u8 - 1) as char),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::{Field, Topology};
+    use crate::sparse::SparseField;
+
+    // 죽은 셀이 아닌 셀의 좌표와 상태
+    fn states(pattern: &Pattern) -> Vec<(usize, usize, u8)> {
+        (0..pattern.cells.len())
+            .filter(|i| pattern.cells[*i] != 0)
+            .map(|i| (i % pattern.width, i / pattern.width, pattern.cells[i]))
+            .collect()
+    }
+
+    #[test]
+    fn parse_rle_glider() {
+        let pattern =
+            Pattern::parse_rle("#N Glider\nx = 3, y = 3, rule = B3/S23\nbo$2bo$3o!").unwrap();
+        assert_eq!((pattern.width, pattern.height), (3, 3));
+        assert_eq!(
+            states(&pattern),
+            [(1, 0, 1), (2, 1, 1), (0, 2, 1), (1, 2, 1), (2, 2, 1)]
+        );
+        assert_eq!(pattern.rule, Some(Rule::parse("B3/S23").unwrap()));
+    }
+
+    #[test]
+    fn parse_rle_runs_across_lines() {
+        let pattern = Pattern::parse_rle("x = 12, y = 4\n10b2o2$\n2o!").unwrap();
+        assert_eq!((pattern.width, pattern.height), (12, 4));
+        assert_eq!(
+            states(&pattern),
+            [(10, 0, 1), (11, 0, 1), (0, 2, 1), (1, 2, 1)]
+        );
+        assert_eq!(pattern.rule, None);
+
+        // Larger than Life의 규칙에는 쉼표가 들어간다
+        let pattern =
+            Pattern::parse_rle("x = 1, y = 1, rule = R2,C0,M1,S6..11,B5..6,NM\no!").unwrap();
+        assert_eq!(
+            pattern.rule,
+            Some(Rule::parse("R2,C0,M1,S6..11,B5..6,NM").unwrap())
+        );
+    }
+
+    #[test]
+    fn parse_rle_multi_state() {
+        let pattern = Pattern::parse_rle("x = 5, y = 2, rule = B2/S/C3\n.AB$2BpA!").unwrap();
+        assert_eq!(
+            states(&pattern),
+            [(1, 0, 1), (2, 0, 2), (0, 1, 2), (1, 1, 2), (2, 1, 25)]
+        );
+    }
+
+    #[test]
+    fn reject_invalid_rle() {
+        assert!(Pattern::parse_rle("").is_err());
+        assert!(Pattern::parse_rle("x = 3\nooo!").is_err());
+        assert!(Pattern::parse_rle("x = 3, y = 1, rule = B9/S\nooo!").is_err());
+        assert!(Pattern::parse_rle("x = 3, y = 1\no?o!").is_err());
+        assert!(Pattern::parse_rle("x = 3, y = 1\npo!").is_err());
+        assert!(Pattern::parse_rle("x = 3, y = 1\nyX!").is_err());
+    }
+
+    #[test]
+    fn parse_plaintext_cells() {
+        let pattern = Pattern::parse_plaintext("!Name: Glider\n.O\n..O\nOOO\n").unwrap();
+        assert_eq!((pattern.width, pattern.height), (3, 3));
+        assert_eq!(
+            states(&pattern),
+            [(1, 0, 1), (2, 1, 1), (0, 2, 1), (1, 2, 1), (2, 2, 1)]
+        );
+        assert!(Pattern::parse_plaintext("..O\n.X.\n").is_err());
+    }
+
+    #[test]
+    fn rle_round_trip() {
+        let mut pattern = Pattern::named("gosper-gun").unwrap();
+        pattern.rule = Some(Rule::default());
+        let text = pattern.to_rle(&["Generation: 30".to_string()]);
+        assert!(text.starts_with("#C Generation: 30\nx = 36, y = 9, rule = B3/S23\n"));
+        assert!(text.lines().all(|line| line.len() <= 70));
+        let parsed = Pattern::parse_rle(&text).unwrap();
+        assert_eq!((parsed.width, parsed.height), (36, 9));
+        assert_eq!(parsed.cells, pattern.cells);
+        assert_eq!(parsed.rule, pattern.rule);
+    }
+
+    #[test]
+    fn multi_state_rle_round_trip() {
+        let rule = Rule::parse("B2/S/C30").unwrap();
+        let mut pattern = Pattern::from_coordinates(
+            6,
+            3,
+            &[
+                (0, 0, 1),
+                (1, 0, 2),
+                (2, 0, 2),
+                (5, 0, 24),
+                (3, 2, 25),
+                (4, 2, 29),
+            ],
+        );
+        pattern.rule = Some(rule);
+        let text = pattern.to_rle(&[]);
+        assert!(text.contains("A2B2.X2$3.pApE!"), "{text}");
+        let parsed = Pattern::parse_rle(&text).unwrap();
+        assert_eq!(parsed.cells, pattern.cells);
+        assert_eq!(parsed.rule, Some(rule));
+
+        // 2상태의 규칙이면 살아 있는 셀만 o로 쓴다
+        pattern.rule = Some(Rule::default());
+        assert!(pattern.to_rle(&[]).ends_with("\no!\n"));
+    }
+
+    #[test]
+    fn multi_state_tags() {
+        assert_eq!(multi_state_tag(0), ".");
+        assert_eq!(multi_state_tag(1), "A");
+        assert_eq!(multi_state_tag(24), "X");
+        assert_eq!(multi_state_tag(25), "pA");
+        assert_eq!(multi_state_tag(255), "yO");
+    }
+
+    #[test]
+    fn plaintext_round_trip() {
+        let mut pattern = Pattern::named("lwss").unwrap();
+        pattern.rule = Some(Rule::parse("B36/S23").unwrap());
+        let text = pattern.to_plaintext(&["Generation: 4".to_string()]);
+        assert!(text.starts_with("!Rule: B36/S23\n!Generation: 4\n.O..O\n"));
+        let parsed = Pattern::parse_plaintext(&text).unwrap();
+        assert_eq!((parsed.width, parsed.height), (5, 4));
+        assert_eq!(parsed.cells, pattern.cells);
+    }
+
+    #[test]
+    fn transform_glider() {
+        let glider = Pattern::named("glider").unwrap();
+        assert_eq!(
+            states(&glider.transformed(90, false)),
+            [(0, 0, 1), (0, 1, 1), (2, 1, 1), (0, 2, 1), (1, 2, 1)]
+        );
+        assert_eq!(
+            states(&glider.transformed(0, true)),
+            [(1, 0, 1), (0, 1, 1), (0, 2, 1), (1, 2, 1), (2, 2, 1)]
+        );
+        assert_eq!(glider.transformed(360, false).cells, glider.cells);
+    }
+
+    // 엔진의 셀을 RLE와 플레인 텍스트의 파일에 써내고 다시 읽어 들인다
+    fn export_and_load(engine: &dyn Engine, rule: Rule, name: &str) -> [(Pattern, String); 2] {
+        let mut pattern = Pattern::from_engine(engine, false);
+        pattern.rule = Some(rule);
+        let comments = ["Generation: 42".to_string()];
+        ["rle", "cells"].map(|extension| {
+            let path = std::env::temp_dir().join(format!(
+                "ch2_game_of_life_{name}_{}.{extension}",
+                std::process::id()
+            ));
+            let path = path.to_str().unwrap();
+            pattern.save(path, &comments).unwrap();
+            let text = fs::read_to_string(path).unwrap();
+            let loaded = Pattern::load(path).unwrap();
+            fs::remove_file(path).unwrap();
+            (loaded, text)
+        })
+    }
+
+    #[test]
+    fn export_whole_field() {
+        // 표시 범위와 관계없이 필드의 구석에 있는 셀도 써낸다
+        let mut field = Field::new(40, 30, Topology::Torus);
+        for (x, y) in [(0, 0), (39, 0), (20, 15), (21, 15), (0, 29), (39, 29)] {
+            field.set(x, y, true);
+        }
+        let rule = Rule::parse("B36/S23").unwrap();
+        for (loaded, text) in export_and_load(&field, rule, "field") {
+            assert!(text.contains("Generation: 42"), "{text}");
+            assert_eq!((loaded.width, loaded.height), (40, 30));
+            assert_eq!(loaded.rule, Some(rule));
+            let mut copy = Field::new(40, 30, Topology::Torus);
+            loaded.transfer(&mut copy, 0, 0);
+            assert_eq!(copy.live_cells(), field.live_cells());
+        }
+    }
+
+    #[test]
+    fn export_unbounded_bounding_box() {
+        // 끝이 없는 우주는 음의 좌표를 포함한 셀의 범위를 써낸다
+        let mut sparse = SparseField::new();
+        for (x, y) in [(-100, -50), (-99, -50), (300, 7)] {
+            sparse.set(x, y, true);
+        }
+        for (loaded, text) in export_and_load(&sparse, Rule::default(), "sparse") {
+            assert!(text.contains("Generation: 42"), "{text}");
+            assert_eq!((loaded.width, loaded.height), (401, 58));
+            assert_eq!(loaded.rule, Some(Rule::default()));
+            let mut copy = SparseField::new();
+            loaded.transfer(&mut copy, -100, -50);
+            assert_eq!(copy.live_cells().len(), 3);
+            assert!(
+                [(-100, -50), (-99, -50), (300, 7)]
+                    .iter()
+                    .all(|(x, y)| copy.get(*x, *y))
+            );
+        }
+    }
+
+    #[test]
+    fn export_generations_field() {
+        let mut field = Field::new(10, 10, Topology::Plane);
+        field.set_state(2, 3, 1);
+        field.set_state(3, 3, 2);
+        field.set_state(9, 9, 2);
+        let pattern = Pattern::from_engine(&field, true);
+        assert_eq!((pattern.width, pattern.height), (8, 7));
+
+        // 죽어 가는 셀은 RLE에만 남는다
+        let rule = Rule::parse("B2/S/C3").unwrap();
+        let [(rle, _), (plaintext, _)] = export_and_load(&field, rule, "generations");
+        assert_eq!(rle.cells, field.cells);
+        assert_eq!(rle.rule, Some(rule));
+        assert_eq!(states(&plaintext), [(2, 3, 1)]);
+    }
+
+    #[test]
+    fn transfer_keeps_existing_cells() {
+        let mut field = Field::new(8, 8, Topology::Plane);
+        field.set(1, 1, true);
+        field.set(3, 3, true);
+        field.set_state(4, 3, 2);
+
+        // 직사각형 안의 죽은 셀은 원래의 셀을 지우지 않는다
+        Pattern::named("glider").unwrap().transfer(&mut field, 2, 2);
+        assert_eq!(
+            field.live_cells(),
+            [(1, 1), (3, 2), (3, 3), (4, 3), (2, 4), (3, 4), (4, 4)]
+        );
+
+        let pattern = Pattern::parse_rle("x = 2, y = 1, rule = B2/S/C3\nAB!").unwrap();
+        pattern.transfer(&mut field, 6, 6);
+        assert_eq!(field.state(6, 6), 1);
+        assert_eq!(field.state(7, 6), 2);
+    }
+}
@@ -0,0 +1,266 @@
+use std::io::{self, Write};
+
+use crate::engine::Engine;
+
+// 셀을 터미널의 글자로 나타내는 방법
+#[derive(Clone, Copy, PartialEq)]
+pub enum RenderMode {
+    Cell,      // 셀 하나를「■」「□」한 글자(2칸)로 그리는 원래의 방식
+    HalfBlock, // 위아래 2셀을「▀」「▄」「█」한 글자로 그린다
+    Braille,   // 2x4셀을 점자 한 글자로 그린다
+}
+
+impl RenderMode {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().as_str() {
+            "cell" => Ok(RenderMode::Cell),
+            "half" | "halfblock" => Ok(RenderMode::HalfBlock),
+            "braille" => Ok(RenderMode::Braille),
+            _ => Err(format!(
+                "알 수 없는 표시 방법 \"{name}\": cell, half, braille 중 하나를 지정하세요"
+            )),
+        }
+    }
+
+    // 한 글자가 나타내는 셀의 수(가로, 세로)
+    pub fn cells_per_char(&self) -> (usize, usize) {
+        match self {
+            RenderMode::Cell => (1, 1),
+            RenderMode::HalfBlock => (1, 2),
+            RenderMode::Braille => (2, 4),
+        }
+    }
+
+    // 한 글자가 차지하는 터미널의 칸 수
+    pub fn char_width(&self) -> usize {
+        match self {
+            RenderMode::Cell => 2,
+            RenderMode::HalfBlock | RenderMode::Braille => 1,
+        }
+    }
+}
+
+// Generations 규칙에서 죽어 가는 셀(상태 2, 3, ...)을 칠하는 256색 팔레트의 번호
+const DYING_COLOURS: [u8; 7] = [196, 208, 226, 46, 51, 21, 201];
+
+// 셀의 상태를 칠하는 색. 죽은 셀과 살아 있는 셀은 터미널의 기본색(None)을 쓴다
+fn state_colour(state: u8) -> Option<u8> {
+    (state >= 2).then(|| DYING_COLOURS[(state as usize - 2) % DYING_COLOURS.len()])
+}
+
+// 화면의 한 글자. highlighted면 반전하여 커서를 나타낸다
+#[derive(Clone, Copy, PartialEq)]
+struct Glyph {
+    ch: char,
+    foreground: Option<u8>, // 글자의 색
+    background: Option<u8>, // 배경의 색
+    highlighted: bool,
+}
+
+// 직전에 그린 화면을 기억해 두고 바뀐 글자만 커서 이동으로 다시 쓰는 렌더러
+// 육각형 격자는 표시 방법에 관계없이 셀 하나를 한 글자로 그리고 홀수 행을 반 글자(1칸) 어긋나게 한다
+pub struct Renderer {
+    mode: RenderMode,
+    hexagonal: bool,
+    screen: Vec<Vec<Glyph>>, // 직전에 그린 필드의 글자
+    status: Vec<String>,     // 직전에 그린 상태 행
+}
+
+impl Renderer {
+    pub fn new(mode: RenderMode) -> Self {
+        Self {
+            mode,
+            hexagonal: false,
+            screen: Vec::new(),
+            status: Vec::new(),
+        }
+    }
+
+    // 육각형 격자를 그릴지 여부를 바꾼다. 바뀌었으면 다음에 모든 글자를 다시 그린다
+    pub fn set_hexagonal(&mut self, hexagonal: bool) {
+        if self.hexagonal != hexagonal {
+            self.hexagonal = hexagonal;
+            self.screen.clear();
+        }
+    }
+
+    // 실제로 쓰는 표시 방법
+    fn mode(&self) -> RenderMode {
+        if self.hexagonal {
+            RenderMode::Cell
+        } else {
+            self.mode
+        }
+    }
+
+    // 터미널의 columns x rows 칸에서 상태 행을 뺀 곳에 들어가는 셀의 수(폭, 높이)
+    pub fn capacity(&self, columns: usize, rows: usize, status_lines: usize) -> (usize, usize) {
+        let mode = self.mode();
+        let (cells_x, cells_y) = mode.cells_per_char();
+        // 육각형 격자에서는 홀수 행이 어긋나는 1칸을 남겨 둔다
+        let columns = columns.saturating_sub(self.hexagonal as usize);
+        (
+            (columns / mode.char_width()).max(1) * cells_x,
+            rows.saturating_sub(status_lines).max(1) * cells_y,
+        )
+    }
+
+    // (x, y)부터 시작하는 한 글자분의 셀을 글자로 바꾼다
+    fn glyph(&self, engine: &dyn Engine, x: i64, y: i64, bottom: i64, cursor: (i64, i64)) -> Glyph {
+        let (cells_x, cells_y) = self.mode().cells_per_char();
+        let highlighted = (x..x + cells_x as i64).contains(&cursor.0)
+            && (y..y + cells_y as i64).contains(&cursor.1);
+        let state = |dx: i64, dy: i64| {
+            if y + dy < bottom {
+                engine.state(x + dx, y + dy)
+            } else {
+                0
+            }
+        };
+        let (ch, foreground, background) = match self.mode() {
+            // [4-1-4]셀이 살아 있으면「■」를, 죽어 있으면「□」를 그립니다
+            // 커서가 있는 셀은「◆」와「◇」로, 죽어 가는 셀은 상태마다의 색의「▣」로 그린다
+            RenderMode::Cell => match (highlighted, state(0, 0)) {
+                (true, 0) => ('◇', None, None),
+                (true, _) => ('◆', state_colour(state(0, 0)), None),
+                (false, 0) => ('□', None, None),
+                (false, 1) => ('■', None, None),
+                (false, dying) => ('▣', state_colour(dying), None),
+            },
+            // 위아래의 셀이 다른 색이면 위를 글자의 색, 아래를 배경의 색으로 칠한다
+            RenderMode::HalfBlock => {
+                let (top, bottom) = (state(0, 0), state(0, 1));
+                let (top_colour, bottom_colour) = (state_colour(top), state_colour(bottom));
+                match (top, bottom) {
+                    (0, 0) => (' ', None, None),
+                    (_, 0) => ('▀', top_colour, None),
+                    (0, _) => ('▄', bottom_colour, None),
+                    _ if top_colour == bottom_colour => ('█', top_colour, None),
+                    _ if top_colour.is_none() => ('▀', None, bottom_colour),
+                    _ => ('▄', bottom_colour, top_colour),
+                }
+            }
+            // 점마다 색을 바꿀 수 없으므로 살아 있는 셀이 없을 때만 죽어 가는 셀의 색으로 칠한다
+            RenderMode::Braille => {
+                // 점자의 각 점에 대응하는 비트(왼쪽 열, 오른쪽 열의 위에서부터)
+                const DOTS: [[u32; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+                let mut bits = 0;
+                let mut colour = None;
+                let mut any_alive = false;
+                for (dx, column) in DOTS.iter().enumerate() {
+                    for (dy, bit) in column.iter().enumerate() {
+                        let state = state(dx as i64, dy as i64);
+                        if state != 0 {
+                            bits |= bit;
+                        }
+                        any_alive |= state == 1;
+                        colour = colour.or(state_colour(state));
+                    }
+                }
+                let colour = if any_alive { None } else { colour };
+                (char::from_u32(0x2800 + bits).unwrap(), colour, None)
+            }
+        };
+        Glyph {
+            ch,
+            foreground,
+            background,
+            highlighted: highlighted && self.mode() != RenderMode::Cell,
+        }
+    }
+
+    // [4-1]필드를 그리는 함수를 선언한다
+    // 표시 범위 view의 필드와 그 아래의 상태 행을 그린다
+    pub fn draw(
+        &mut self,
+        engine: &dyn Engine,
+        view: (i64, i64, usize, usize),
+        cursor: (i64, i64),
+        status: &[String],
+    ) {
+        let (view_x, view_y, width, height) = view;
+        let mode = self.mode();
+        let (cells_x, cells_y) = mode.cells_per_char();
+        let columns = width.div_ceil(cells_x);
+        let rows = height.div_ceil(cells_y);
+        let bottom = view_y + height as i64;
+
+        let mut out = String::new();
+
+        // 크기가 바뀌었으면 화면을 지우고 모든 글자를 다시 그린다
+        if self.screen.len() != rows || self.screen.first().is_some_and(|row| row.len() != columns)
+        {
+            clearscreen::clear().unwrap();
+            out += "\x1b[?25l"; // 터미널의 커서를 숨긴다
+            let blank = Glyph {
+                ch: '\0',
+                foreground: None,
+                background: None,
+                highlighted: false,
+            };
+            self.screen = vec![vec![blank; columns]; rows];
+            self.status.clear();
+        }
+
+        for row in 0..rows {
+            let y = view_y + (row * cells_y) as i64;
+            let indent = (self.hexagonal && y.rem_euclid(2) == 1) as usize;
+            let mut continuing = false; // 직전의 글자에 이어서 쓰고 있는지 여부
+            for column in 0..columns {
+                let x = view_x + (column * cells_x) as i64;
+                let glyph = self.glyph(engine, x, y, bottom, cursor);
+                if glyph == self.screen[row][column] {
+                    continuing = false;
+                    continue;
+                }
+                if !continuing {
+                    out += &format!(
+                        "\x1b[{};{}H",
+                        row + 1,
+                        column * mode.char_width() + indent + 1
+                    );
+                    continuing = true;
+                }
+                // 반전과 색이 있으면 그 글자만 속성을 바꾸어 쓴다
+                let mut attributes = Vec::new();
+                if glyph.highlighted {
+                    attributes.push("7".to_string());
+                }
+                if let Some(colour) = glyph.foreground {
+                    attributes.push(format!("38;5;{colour}"));
+                }
+                if let Some(colour) = glyph.background {
+                    attributes.push(format!("48;5;{colour}"));
+                }
+                if attributes.is_empty() {
+                    out.push(glyph.ch);
+                } else {
+                    out += &format!("\x1b[{}m{}\x1b[0m", attributes.join(";"), glyph.ch);
+                }
+                self.screen[row][column] = glyph;
+            }
+        }
+
+        // 상태 행은 바뀐 행만 다시 쓰고 행 끝까지 지운다
+        self.status.resize(status.len(), String::new());
+        for (i, line) in status.iter().enumerate() {
+            if self.status[i] != *line {
+                out += &format!("\x1b[{};1H{}\x1b[K", rows + i + 1, line);
+                self.status[i] = line.clone();
+            }
+        }
+
+        // 한 번에 써내어 깜빡이지 않게 한다
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(out.as_bytes()).unwrap();
+        stdout.flush().unwrap();
+    }
+
+    // 터미널의 커서를 다시 보이게 하고 화면을 지운다
+    pub fn finish(&mut self) {
+        clearscreen::clear().unwrap();
+        print!("\x1b[?25h");
+        self.screen.clear();
+        self.status.clear();
+    }
+}
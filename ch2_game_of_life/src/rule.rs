@@ -0,0 +1,524 @@
+use std::fmt;
+use std::sync::LazyLock;
+
+use crate::neighbourhood::Neighbourhood;
+
+// 이름이 붙은 대표적인 규칙(이름, 규칙 문자열)
+pub const PRESETS: [(&str, &str); 13] = [
+    ("Conway's Life", "B3/S23"),
+    ("HighLife", "B36/S23"),
+    ("Seeds", "B2/S"),
+    ("Day & Night", "B3678/S34678"),
+    ("Life without Death", "B3/S012345678"),
+    ("Maze", "B3/S12345"),
+    ("2x2", "B36/S125"),
+    ("Replicator", "B1357/S1357"),
+    ("Brian's Brain", "B2/S/C3"),
+    ("Star Wars", "B2/S345/C4"),
+    ("Hexagonal Life", "B2/S34H"),
+    ("Von Neumann Life", "B3/S23V"),
+    ("Bosco's Rule", "R5,C0,M1,S34..58,B34..45,NM"),
+];
+
+// 해석해 둔 프리셋. 상태 표시줄이 매 프레임 이름을 찾으므로 처음 한 번만 해석한다
+static PARSED_PRESETS: LazyLock<Vec<(&str, Rule)>> = LazyLock::new(|| {
+    PRESETS
+        .iter()
+        .map(|(name, rulestring)| (*name, Rule::parse(rulestring).unwrap()))
+        .collect()
+});
+
+// 탄생과 생존 조건을 이웃 중 살아 있는 셀의 수를 비트 위치로 하는 비트 마스크로 보유하는 규칙
+// 이웃의 수는 neighbourhood에 따라 달라지며 최대 127까지 다룬다
+// Generations 규칙(「B2/S/C3」)에서는 살아남지 못한 셀이 곧바로 죽지 않고
+// 2, 3, ..., states - 1의 죽어 가는 상태를 거친다. 이웃으로 세는 것은 살아 있는 셀(상태 1)뿐이다
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rule {
+    birth: u128,
+    survival: u128,
+    states: u8, // 죽은 셀과 살아 있는 셀을 포함한 상태의 수. 보통의 라이프 게임은 2
+    neighbourhood: Neighbourhood,
+}
+
+impl Rule {
+    // 「B3/S23」형식의 규칙 문자열을 해석한다
+    // 대소문자는 구별하지 않으며 「S23/B3」처럼 순서가 바뀌어도 된다
+    // 「B2/S/C3」처럼 C(또는 G) 부분이 있으면 Generations 규칙이 된다
+    // 끝에 V를 붙이면 폰 노이만 근방, H를 붙이면 육각형 격자의 규칙이 된다(예: B2/S34H)
+    // 「R2,C0,M1,S6..11,B5..6,NM」처럼 R로 시작하면 범위가 넓은 Larger than Life 규칙이다
+    // 프리셋의 이름(예:「highlife」)도 받아들인다
+    pub fn parse(rulestring: &str) -> Result<Self, String> {
+        let rulestring = rulestring.trim();
+        if let Some((_, preset)) = PRESETS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(rulestring))
+        {
+            return Self::parse(preset);
+        }
+        if rulestring.starts_with(['R', 'r']) && rulestring.contains(',') {
+            return Self::parse_larger_than_life(rulestring);
+        }
+
+        // 끝의 V나 H는 근방을 나타낸다
+        let (body, neighbourhood) = match rulestring.chars().last().map(|c| c.to_ascii_uppercase())
+        {
+            Some('V') => (
+                &rulestring[..rulestring.len() - 1],
+                Neighbourhood::VonNeumann(1),
+            ),
+            Some('H') => (
+                &rulestring[..rulestring.len() - 1],
+                Neighbourhood::Hexagonal,
+            ),
+            _ => (rulestring, Neighbourhood::Moore(1)),
+        };
+        let suffix = &rulestring[body.len()..];
+
+        // 오래된 RLE 파일에서 쓰이는 「23/3」(생존/탄생) 형식은 「B3/S23」으로 바꿔 해석한다
+        if let Some((s, b)) = body.split_once('/')
+            && s.chars().chain(b.chars()).all(|c| c.is_ascii_digit())
+        {
+            return Self::parse(&format!("B{b}/S{s}{suffix}"));
+        }
+        // Generations 규칙의 「2/2/3」(생존/탄생/상태 수) 형식도 마찬가지로 바꾼다
+        if let [s, b, c] = body.split('/').collect::<Vec<_>>()[..]
+            && s.chars()
+                .chain(b.chars())
+                .chain(c.chars())
+                .all(|c| c.is_ascii_digit())
+        {
+            return Self::parse(&format!("B{b}/S{s}/C{c}{suffix}"));
+        }
+
+        let mut birth = None;
+        let mut survival = None;
+        let mut states = None;
+        let size = neighbourhood.size();
+
+        for part in body.split('/') {
+            let mut chars = part.chars();
+            let table = match chars.next().map(|c| c.to_ascii_uppercase()) {
+                Some('B') => &mut birth,
+                Some('S') => &mut survival,
+                Some('C') | Some('G') => {
+                    if states.is_some() {
+                        return Err(format!(
+                            "잘못된 규칙 문자열 \"{rulestring}\": \"{part}\"이(가) 중복되었습니다"
+                        ));
+                    }
+                    states = Some(parse_states(rulestring, chars.as_str())?);
+                    continue;
+                }
+                _ => {
+                    return Err(format!(
+                        "잘못된 규칙 문자열 \"{rulestring}\": 각 부분은 B, S 또는 C로 시작해야 합니다 (예: B3/S23)"
+                    ));
+                }
+            };
+            if table.is_some() {
+                return Err(format!(
+                    "잘못된 규칙 문자열 \"{rulestring}\": \"{part}\"이(가) 중복되었습니다"
+                ));
+            }
+
+            let mut counts = 0;
+            for c in chars {
+                match c.to_digit(10) {
+                    Some(n) if n as usize <= size => counts |= 1 << n,
+                    _ => {
+                        return Err(format!(
+                            "잘못된 규칙 문자열 \"{rulestring}\": '{c}'은(는) 0~{size}의 이웃 수가 아닙니다"
+                        ));
+                    }
+                }
+            }
+            *table = Some(counts);
+        }
+
+        match (birth, survival) {
+            (Some(birth), Some(survival)) => Ok(Self {
+                birth,
+                survival,
+                states: states.unwrap_or(2),
+                neighbourhood,
+            }),
+            _ => Err(format!(
+                "잘못된 규칙 문자열 \"{rulestring}\": B와 S 부분이 모두 필요합니다 (예: B3/S23)"
+            )),
+        }
+    }
+
+    // 「R2,C0,M1,S6..11,B5..6,NM」형식의 Larger than Life 규칙을 해석한다
+    // R은 범위, C는 상태의 수(0이나 2면 보통의 규칙), M1은 자기 자신도 세는 것,
+    // S와 B는 쉼표로 구분한 이웃 수나 「a..b」의 범위, N은 근방(M: 무어, N: 폰 노이만, H: 육각형)이다
+    fn parse_larger_than_life(rulestring: &str) -> Result<Self, String> {
+        let error = |message: &str| format!("잘못된 규칙 문자열 \"{rulestring}\": {message}");
+        let mut range = None;
+        let mut states = 2;
+        let mut middle = false;
+        let mut birth = None;
+        let mut survival = None;
+        let mut kind = 'M';
+        // 지금 읽고 있는 S나 B의 목록
+        let mut table: Option<&mut Option<u128>> = None;
+
+        for item in rulestring.split(',') {
+            let item = item.trim();
+            let Some(key) = item.chars().next().filter(|c| c.is_ascii_alphabetic()) else {
+                // 앞의 S나 B에 이어지는 이웃 수
+                let Some(Some(counts)) = table.as_deref_mut() else {
+                    return Err(error(&format!(
+                        "\"{item}\"이(가) 어느 부분의 값인지 알 수 없습니다"
+                    )));
+                };
+                *counts |= parse_counts(item).ok_or_else(|| {
+                    error(&format!("\"{item}\"은(는) 0~127의 이웃 수가 아닙니다"))
+                })?;
+                continue;
+            };
+            let value = &item[1..];
+            table = None;
+            match key.to_ascii_uppercase() {
+                'R' => {
+                    range = Some(
+                        value
+                            .parse::<u8>()
+                            .ok()
+                            .filter(|r| *r >= 1)
+                            .ok_or_else(|| error("범위 R은 1 이상이어야 합니다"))?,
+                    )
+                }
+                'C' => {
+                    states = match value {
+                        "0" => 2,
+                        _ => parse_states(rulestring, value)?,
+                    }
+                }
+                'M' => {
+                    middle = match value {
+                        "0" => false,
+                        "1" => true,
+                        _ => return Err(error("M은 0이나 1이어야 합니다")),
+                    }
+                }
+                'S' | 'B' => {
+                    let target = if key.eq_ignore_ascii_case(&'S') {
+                        &mut survival
+                    } else {
+                        &mut birth
+                    };
+                    if target.is_some() {
+                        return Err(error(&format!("\"{key}\" 부분이 중복되었습니다")));
+                    }
+                    *target = Some(if value.is_empty() {
+                        0
+                    } else {
+                        parse_counts(value).ok_or_else(|| {
+                            error(&format!("\"{value}\"은(는) 0~127의 이웃 수가 아닙니다"))
+                        })?
+                    });
+                    table = Some(target);
+                }
+                'N' => {
+                    kind = match value.to_ascii_uppercase().as_str() {
+                        "M" => 'M',
+                        "N" => 'N',
+                        "H" => 'H',
+                        _ => return Err(error("근방 N은 M, N, H 중 하나여야 합니다")),
+                    }
+                }
+                _ => {
+                    return Err(error(&format!(
+                        "\"{item}\"은(는) R, C, M, S, B, N 중 어느 것으로도 시작하지 않습니다"
+                    )));
+                }
+            }
+        }
+
+        let range = range.ok_or_else(|| error("범위 R이 필요합니다"))?;
+        let neighbourhood = match kind {
+            'M' if range <= Neighbourhood::MAX_MOORE_RANGE => Neighbourhood::Moore(range),
+            'N' if range <= Neighbourhood::MAX_VON_NEUMANN_RANGE => {
+                Neighbourhood::VonNeumann(range)
+            }
+            'H' if range == 1 => Neighbourhood::Hexagonal,
+            'M' => {
+                return Err(error(&format!(
+                    "무어 근방의 범위는 1~{}여야 합니다",
+                    Neighbourhood::MAX_MOORE_RANGE
+                )));
+            }
+            'N' => {
+                return Err(error(&format!(
+                    "폰 노이만 근방의 범위는 1~{}여야 합니다",
+                    Neighbourhood::MAX_VON_NEUMANN_RANGE
+                )));
+            }
+            _ => return Err(error("육각형 근방의 범위는 1이어야 합니다")),
+        };
+        let (Some(birth), Some(mut survival)) = (birth, survival) else {
+            return Err(error(
+                "B와 S 부분이 모두 필요합니다 (예: R2,C0,M0,S6..9,B7..8,NM)",
+            ));
+        };
+        // 자기 자신도 세는 규칙이면 살아 있는 셀의 수는 이웃의 수보다 1 많다
+        if middle {
+            survival >>= 1;
+        }
+        let size = neighbourhood.size();
+        if (birth | survival) >> (size + 1) != 0 {
+            return Err(error(&format!("이웃 수는 0~{size}여야 합니다")));
+        }
+        Ok(Self {
+            birth,
+            survival,
+            states,
+            neighbourhood,
+        })
+    }
+
+    // 현재 셀의 상태와 인접하는 살아 있는 셀의 수로 다음 세대의 상태를 구한다
+    pub fn next_state(&self, alive: bool, living_cell_count: usize) -> bool {
+        if alive {
+            self.survival >> living_cell_count & 1 != 0
+        } else {
+            self.birth >> living_cell_count & 1 != 0
+        }
+    }
+
+    // 상태의 수. 2보다 크면 Generations 규칙이다
+    pub fn states(&self) -> u8 {
+        self.states
+    }
+
+    // 이웃으로 세는 셀의 범위
+    pub fn neighbourhood(&self) -> Neighbourhood {
+        self.neighbourhood
+    }
+
+    // 셀의 상태(0: 죽은 셀, 1: 살아 있는 셀, 2 이상: 죽어 가는 셀)와
+    // 인접하는 살아 있는 셀의 수로 다음 세대의 상태를 구한다
+    pub fn next_cell(&self, state: u8, living_cell_count: usize) -> u8 {
+        match state {
+            0 => self.next_state(false, living_cell_count) as u8,
+            1 if self.next_state(true, living_cell_count) => 1,
+            // 죽어 가는 상태를 하나씩 진행하여 마지막 다음은 죽은 셀이 된다
+            _ => (state + 1) % self.states,
+        }
+    }
+
+    // 프리셋에 있는 규칙이면 그 이름을 반환한다
+    pub fn name(&self) -> Option<&'static str> {
+        PARSED_PRESETS
+            .iter()
+            .find(|(_, rule)| rule == self)
+            .map(|(name, _)| *name)
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Self::parse(PRESETS[0].1).unwrap()
+    }
+}
+
+// 「B3/S23」형식으로 표시한다. Generations 규칙이면 「/C3」처럼 상태의 수를 붙이고
+// 폰 노이만 근방이면 V, 육각형 격자면 H를 끝에 붙인다
+// 범위가 2 이상이면 「R2,C0,M0,S6..9,B7..8,NM」형식으로 표시한다
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (range, kind) = match self.neighbourhood {
+            Neighbourhood::Moore(range) => (range, "M"),
+            Neighbourhood::VonNeumann(range) => (range, "N"),
+            Neighbourhood::Hexagonal => (1, "H"),
+        };
+        if range > 1 {
+            let states = if self.states > 2 { self.states } else { 0 };
+            return write!(
+                f,
+                "R{range},C{states},M0,S{},B{},N{kind}",
+                format_counts(self.survival),
+                format_counts(self.birth)
+            );
+        }
+
+        write!(f, "B")?;
+        for n in (0..128).filter(|n| self.birth >> n & 1 != 0) {
+            write!(f, "{n}")?;
+        }
+        write!(f, "/S")?;
+        for n in (0..128).filter(|n| self.survival >> n & 1 != 0) {
+            write!(f, "{n}")?;
+        }
+        if self.states > 2 {
+            write!(f, "/C{}", self.states)?;
+        }
+        match self.neighbourhood {
+            Neighbourhood::VonNeumann(_) => write!(f, "V"),
+            Neighbourhood::Hexagonal => write!(f, "H"),
+            Neighbourhood::Moore(_) => Ok(()),
+        }
+    }
+}
+
+// C 부분의 상태의 수를 해석한다
+fn parse_states(rulestring: &str, value: &str) -> Result<u8, String> {
+    value
+        .parse::<u8>()
+        .ok()
+        .filter(|n| *n >= 2)
+        .ok_or_else(|| format!("잘못된 규칙 문자열 \"{rulestring}\": 상태의 수는 2~255여야 합니다"))
+}
+
+// 「5」나 「5..9」를 이웃 수의 비트 마스크로 바꾼다
+fn parse_counts(value: &str) -> Option<u128> {
+    let (low, high) = value.split_once("..").unwrap_or((value, value));
+    let (low, high) = (low.parse::<u32>().ok()?, high.parse::<u32>().ok()?);
+    (low <= high && high < 128).then(|| (low..=high).fold(0, |counts, n| counts | 1 << n))
+}
+
+// 이웃 수의 비트 마스크를 「2,5..9」처럼 연속하는 부분을 범위로 묶어 표시한다
+fn format_counts(counts: u128) -> String {
+    let mut parts = Vec::new();
+    let mut n = 0;
+    while n < 128 {
+        if counts >> n & 1 == 0 {
+            n += 1;
+            continue;
+        }
+        let start = n;
+        while n + 1 < 128 && counts >> (n + 1) & 1 != 0 {
+            n += 1;
+        }
+        parts.push(if start == n {
+            format!("{n}")
+        } else {
+            format!("{start}..{n}")
+        });
+        n += 1;
+    }
+    parts.join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 살아남는 이웃 수와 태어나는 이웃 수를 0~8에서 모은다
+    fn counts(rule: &Rule) -> (Vec<usize>, Vec<usize>) {
+        (
+            (0..=8).filter(|n| rule.next_state(false, *n)).collect(),
+            (0..=8).filter(|n| rule.next_state(true, *n)).collect(),
+        )
+    }
+
+    #[test]
+    fn parse_birth_survival() {
+        let rule = Rule::parse("B3/S23").unwrap();
+        assert_eq!(counts(&rule), (vec![3], vec![2, 3]));
+        assert_eq!(rule.states(), 2);
+        assert_eq!(rule.neighbourhood(), Neighbourhood::Moore(1));
+        assert_eq!(rule.name(), Some("Conway's Life"));
+
+        assert_eq!(
+            counts(&Rule::parse("b36/s23").unwrap()),
+            (vec![3, 6], vec![2, 3])
+        );
+        assert_eq!(Rule::parse("S23/B3").unwrap(), rule);
+        assert_eq!(Rule::parse("23/3").unwrap(), rule);
+        assert_eq!(Rule::parse("highlife").unwrap().to_string(), "B36/S23");
+        assert_eq!(counts(&Rule::parse("B2/S").unwrap()), (vec![2], vec![]));
+        assert_eq!(
+            Rule::parse("B3678/S34678").unwrap().to_string(),
+            "B3678/S34678"
+        );
+    }
+
+    #[test]
+    fn parse_neighbourhood_suffix() {
+        let rule = Rule::parse("B2/S34H").unwrap();
+        assert_eq!(rule.neighbourhood(), Neighbourhood::Hexagonal);
+        assert_eq!(rule.to_string(), "B2/S34H");
+        let rule = Rule::parse("B3/S23V").unwrap();
+        assert_eq!(rule.neighbourhood(), Neighbourhood::VonNeumann(1));
+        assert!(Rule::parse("B5/S23V").is_err());
+    }
+
+    #[test]
+    fn parse_generations() {
+        let rule = Rule::parse("B2/S/C3").unwrap();
+        assert_eq!(rule.states(), 3);
+        assert_eq!(rule.name(), Some("Brian's Brain"));
+        assert_eq!(Rule::parse("/2/3").unwrap(), rule);
+        assert_eq!(Rule::parse("B2/S/G3").unwrap(), rule);
+        assert_eq!(rule.to_string(), "B2/S/C3");
+
+        // 살아남지 못한 셀은 죽어 가는 상태를 거쳐 죽은 셀이 된다
+        assert_eq!(rule.next_cell(0, 2), 1);
+        assert_eq!(rule.next_cell(1, 2), 2);
+        assert_eq!(rule.next_cell(2, 2), 0);
+        assert_eq!(Rule::parse("B2/S345/C4").unwrap().next_cell(1, 4), 1);
+    }
+
+    #[test]
+    fn parse_larger_than_life() {
+        let rule = Rule::parse("R5,C0,M1,S34..58,B34..45,NM").unwrap();
+        assert_eq!(rule.neighbourhood(), Neighbourhood::Moore(5));
+        assert_eq!(rule.states(), 2);
+        assert!(!rule.next_state(false, 33));
+        assert!(rule.next_state(false, 34) && rule.next_state(false, 45));
+        assert!(!rule.next_state(false, 46));
+        // M1이므로 생존 조건은 자기 자신을 뺀 33..57이 된다
+        assert!(!rule.next_state(true, 32));
+        assert!(rule.next_state(true, 33) && rule.next_state(true, 57));
+        assert!(!rule.next_state(true, 58));
+        assert_eq!(rule.to_string(), "R5,C0,M0,S33..57,B34..45,NM");
+        assert_eq!(Rule::parse(&rule.to_string()).unwrap(), rule);
+
+        let rule = Rule::parse("R2,C3,M0,S2,4..5,B3,NN").unwrap();
+        assert_eq!(rule.neighbourhood(), Neighbourhood::VonNeumann(2));
+        assert_eq!(rule.states(), 3);
+        assert!(rule.next_state(true, 2) && rule.next_state(true, 5));
+        assert!(!rule.next_state(true, 3));
+    }
+
+    #[test]
+    fn presets_are_named() {
+        for (name, rulestring) in PRESETS {
+            let rule = Rule::parse(rulestring).unwrap();
+            assert_eq!(rule.name(), Some(name));
+            assert_eq!(Rule::parse(&name.to_lowercase()), Ok(rule));
+        }
+        assert_eq!(Rule::parse("B3/S2").unwrap().name(), None);
+    }
+
+    #[test]
+    fn reject_invalid_rulestrings() {
+        for rulestring in [
+            "",
+            "B3",
+            "S23",
+            "B3/S23/B4",
+            "B9/S23",
+            "B3/Sx",
+            "X3/S23",
+            "B2/S/C1",
+            "B2/S/C3/C4",
+            "R2,S2..3",
+            "R0,C0,M0,S1,B1,NM",
+            "R6,C0,M0,S1,B1,NM",
+            "R2,C0,M2,S1,B1,NM",
+            "R2,C0,M0,S30,B1,NM",
+            "R2,C0,M0,S1,B1,NX",
+            "R2,C0,M0,S5..1,B1,NM",
+            "R2,C0,M0,S1,B1,Q1",
+            "R2,C0,M0,S1,S2,B1,NM",
+        ] {
+            let error = Rule::parse(rulestring).unwrap_err();
+            assert!(
+                error.contains("잘못된 규칙 문자열"),
+                "{rulestring}: {error}"
+            );
+        }
+    }
+}
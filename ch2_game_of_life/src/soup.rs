@@ -0,0 +1,496 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::engine::Engine;
+use crate::hashlife::HashLife;
+use crate::pattern::Pattern;
+use crate::rule::Rule;
+use crate::sparse::SparseField;
+use crate::stats::bounding_box;
+
+// 수프 검색에서 하나의 수프를 까는 범위의 한 변
+pub const SOUP_SIZE: usize = 16;
+
+// 수프가 안정되었는지 조사하는 간격과 포기하는 세대 수
+const CHECK_INTERVAL: u64 = 100;
+const MAX_GENERATIONS: u64 = 10000;
+
+// 물체를 분류할 때 찾는 주기의 최댓값
+const MAX_PERIOD: u64 = 60;
+
+// 서로 영향을 줄 수 있는 셀의 거리(체비쇼프 거리). 이보다 떨어진 셀은 다음 세대에 서로 관계하지 않는다
+const INTERACTION_DISTANCE: i64 = 2;
+
+// 잘 알려진 물체의 코드와 이름
+const NAMES: [(&str, &str); 12] = [
+    ("xs4_33", "block"),
+    ("xs6_696", "beehive"),
+    ("xs7_2596", "loaf"),
+    ("xs5_253", "boat"),
+    ("xs6_356", "ship"),
+    ("xs4_252", "tub"),
+    ("xs8_6996", "pond"),
+    ("xp2_7", "blinker"),
+    ("xp2_7e", "toad"),
+    ("xp2_318c", "beacon"),
+    ("xq4_153", "glider"),
+    ("xq4_6frc", "lwss"),
+];
+
+// seed에서 만든 난수로 width x height의 셀을 density의 비율만큼 살린 패턴을 만든다
+// 같은 seed와 density면 언제나 같은 수프가 된다
+// rand의 판이 바뀌어도 수프가 바뀌지 않도록 알고리즘이 정해진 ChaCha8에 seed를 그대로 열쇠로 주고
+// 난수를 셀로 바꾸는 방법도 직접 정한다
+pub fn generate(width: usize, height: usize, density: f64, seed: u64) -> Pattern {
+    let mut key = [0; 32];
+    key[..8].copy_from_slice(&seed.to_le_bytes());
+    let mut rng = ChaCha8Rng::from_seed(key);
+    // 53비트의 난수를 [0, 1)의 값으로 보고 density보다 작으면 살린다
+    let threshold = (density.clamp(0.0, 1.0) * (1_u64 << 53) as f64) as u64;
+    Pattern {
+        width,
+        height,
+        cells: (0..width * height)
+            .map(|_| u8::from(rng.next_u64() >> 11 < threshold))
+            .collect(),
+        rule: None,
+    }
+}
+
+// 바운딩 박스의 왼쪽 위가 (0, 0)이 되도록 옮기고 정렬한 셀
+fn normalise(cells: &[(i64, i64)]) -> Vec<(i64, i64)> {
+    let Some((x0, y0, _, _)) = bounding_box(cells) else {
+        return Vec::new();
+    };
+    let mut cells: Vec<_> = cells.iter().map(|(x, y)| (x - x0, y - y0)).collect();
+    cells.sort_unstable();
+    cells
+}
+
+// 확장 Wechsler 형식으로 부호화한다
+// 5행씩의 띠를 열마다 0~9, a~v의 한 글자(맨 위 행이 1의 비트)로 나타내고 띠 사이는 z로 구분한다
+// 0이 이어지면 w(2개), x(3개), y와 한 글자(4~39개)로 줄이고 띠 끝의 0은 생략한다
+fn wechsler(cells: &[(i64, i64)]) -> String {
+    const DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+    let Some((_, _, width, height)) = bounding_box(cells) else {
+        return String::new();
+    };
+    let cells: HashSet<_> = cells.iter().copied().collect();
+    let mut strips = Vec::new();
+    for strip in 0..height.div_ceil(5) as i64 {
+        let mut columns: Vec<usize> = (0..width as i64)
+            .map(|x| {
+                (0..5)
+                    .filter(|row| cells.contains(&(x, strip * 5 + row)))
+                    .map(|row| 1 << row)
+                    .sum()
+            })
+            .collect();
+        while columns.last() == Some(&0) {
+            columns.pop();
+        }
+
+        let mut text = String::new();
+        let mut i = 0;
+        while i < columns.len() {
+            let zeros = columns[i..].iter().take_while(|c| **c == 0).count().min(39);
+            match zeros {
+                0 => text.push(DIGITS[columns[i]] as char),
+                1 => text.push('0'),
+                2 => text.push('w'),
+                3 => text.push('x'),
+                _ => {
+                    text.push('y');
+                    text.push(DIGITS[zeros - 4] as char);
+                }
+            }
+            i += zeros.max(1);
+        }
+        strips.push(text);
+    }
+    strips.join("z")
+}
+
+// 모든 위상과 8가지 방향 중 가장 짧고, 같은 길이라면 사전 순으로 가장 앞서는 부호를 고른다
+fn canonical(phases: &[Vec<(i64, i64)>]) -> String {
+    // x와 y를 바꿀지 여부와 x, y의 부호의 조합으로 8가지 방향을 만든다
+    let orientations = [false, true].into_iter().flat_map(|swap| {
+        [(1, 1), (-1, 1), (1, -1), (-1, -1)].map(|(sign_x, sign_y)| (swap, sign_x, sign_y))
+    });
+    orientations
+        .flat_map(|(swap, sign_x, sign_y)| {
+            phases.iter().map(move |cells| {
+                let cells: Vec<_> = cells
+                    .iter()
+                    .map(|&(x, y)| if swap { (y, x) } else { (x, y) })
+                    .map(|(x, y)| (x * sign_x, y * sign_y))
+                    .collect();
+                wechsler(&normalise(&cells))
+            })
+        })
+        .min_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)))
+        .unwrap_or_default()
+}
+
+// 하나의 물체를 단독으로 진행시켜 「xs4_33」처럼 종류와 부호를 붙인 코드를 반환한다
+// xs는 고정물(뒤는 셀의 수), xp는 진동자, xq는 우주선(뒤는 주기)이다
+// MAX_PERIOD 세대 안에 같은 모양으로 돌아오지 않으면 None을 반환한다
+fn classify(cells: &[(i64, i64)], rule: &Rule) -> Option<String> {
+    let mut engine = SparseField::new();
+    for (x, y) in cells {
+        engine.set(*x, *y, true);
+    }
+    let origin = bounding_box(cells)?;
+    let start = normalise(cells);
+    let mut phases = vec![start.clone()];
+    for generation in 1..=MAX_PERIOD {
+        engine.step(rule);
+        let live_cells = engine.live_cells();
+        let (x, y, _, _) = bounding_box(&live_cells)?;
+        let shape = normalise(&live_cells);
+        if shape == start {
+            let prefix = if (x, y) != (origin.0, origin.1) {
+                format!("xq{generation}")
+            } else if generation == 1 {
+                format!("xs{}", start.len())
+            } else {
+                format!("xp{generation}")
+            };
+            return Some(format!("{prefix}_{}", canonical(&phases)));
+        }
+        phases.push(shape);
+    }
+    None
+}
+
+// 서로 distance 이내에 있는 셀을 하나로 묶는다. distance가 1이면 8방향으로 이어진 셀이 된다
+fn clusters(cells: &[(i64, i64)], distance: i64) -> Vec<Vec<(i64, i64)>> {
+    let mut remaining: HashSet<_> = cells.iter().copied().collect();
+    let mut clusters = Vec::new();
+    while let Some(&first) = remaining.iter().next() {
+        remaining.remove(&first);
+        let mut cluster = vec![first];
+        let mut i = 0;
+        while i < cluster.len() {
+            let (x, y) = cluster[i];
+            for dy in -distance..=distance {
+                for dx in -distance..=distance {
+                    if remaining.remove(&(x + dx, y + dy)) {
+                        cluster.push((x + dx, y + dy));
+                    }
+                }
+            }
+            i += 1;
+        }
+        clusters.push(cluster);
+    }
+    clusters
+}
+
+// 부분들을 함께 진행시킨 결과가 MAX_PERIOD 세대 동안 따로따로 진행시킨 결과를 합친 것과 같은지 여부
+// 같으면 부분들은 서로 영향을 주지 않는 독립된 물체이다
+fn independent(parts: &[Vec<(i64, i64)>], rule: &Rule) -> bool {
+    let mut joint = SparseField::new();
+    let mut separate: Vec<_> = parts
+        .iter()
+        .map(|part| {
+            let mut engine = SparseField::new();
+            for (x, y) in part {
+                joint.set(*x, *y, true);
+                engine.set(*x, *y, true);
+            }
+            engine
+        })
+        .collect();
+    for _ in 0..MAX_PERIOD {
+        joint.step(rule);
+        let mut expected = Vec::new();
+        for engine in separate.iter_mut() {
+            engine.step(rule);
+            expected.extend(engine.live_cells());
+        }
+        let mut cells = joint.live_cells();
+        cells.sort_unstable();
+        expected.sort_unstable();
+        if cells != expected {
+            return false;
+        }
+    }
+    true
+}
+
+// 서로 가까이 있는 셀의 덩어리를 8방향으로 이어진 부분으로 나누고
+// 서로 영향을 주는 부분끼리만 다시 합쳐서 독립된 물체로 나눈다
+// 고정물이 붙어 있어도 따로 세고, 위상에 따라 떨어지는 비컨 같은 진동자는 하나로 센다
+fn split(cluster: &[(i64, i64)], rule: &Rule) -> Vec<Vec<(i64, i64)>> {
+    let parts = clusters(cluster, 1);
+    if parts.len() == 1 || independent(&parts, rule) {
+        return parts;
+    }
+
+    // 가까운 두 부분씩 조사하여 영향을 주는 것끼리 같은 묶음으로 한다
+    let mut groups: Vec<usize> = (0..parts.len()).collect();
+    let near = |a: &[(i64, i64)], b: &[(i64, i64)]| {
+        a.iter().any(|(ax, ay)| {
+            b.iter().any(|(bx, by)| {
+                (ax - bx).abs() <= INTERACTION_DISTANCE && (ay - by).abs() <= INTERACTION_DISTANCE
+            })
+        })
+    };
+    for i in 0..parts.len() {
+        for j in i + 1..parts.len() {
+            if groups[i] != groups[j]
+                && near(&parts[i], &parts[j])
+                && !independent(&[parts[i].clone(), parts[j].clone()], rule)
+            {
+                let (from, to) = (groups[j], groups[i]);
+                groups
+                    .iter_mut()
+                    .filter(|g| **g == from)
+                    .for_each(|g| *g = to);
+            }
+        }
+    }
+    let mut merged: HashMap<usize, Vec<(i64, i64)>> = HashMap::new();
+    for (part, group) in parts.into_iter().zip(groups) {
+        merged.entry(group).or_default().extend(part);
+    }
+    let merged: Vec<_> = merged.into_values().collect();
+
+    // 셋 이상이 함께 반응하는 경우처럼 두 개씩으로는 나눌 수 없으면 덩어리 전체를 하나로 한다
+    if merged.len() == 1 || independent(&merged, rule) {
+        merged
+    } else {
+        vec![cluster.to_vec()]
+    }
+}
+
+// 셀이 가까이 모인 덩어리의 모양 → 그것을 나눈 물체들
+type Splits = HashMap<Vec<(i64, i64)>, Vec<Vec<(i64, i64)>>>;
+
+// 수프 검색의 결과
+pub struct Census {
+    pub soups: u64,
+    pub generations: u64,                    // 모든 수프를 진행한 세대 수의 합계
+    pub unsettled: u64,                      // MAX_GENERATIONS 세대까지 안정되지 않은 수프의 수
+    pub unclassified: usize,                 // 분류할 수 없었던 물체의 수
+    objects: HashMap<String, usize>,         // 코드 → 나타난 수
+    known: HashMap<Vec<(i64, i64)>, String>, // 한 번 분류한 모양 → 코드
+    splits: Splits,                          // 한 번 나눈 덩어리
+}
+
+impl Census {
+    // rule로 seed부터 연속한 seed의 수프를 soups개 진행시켜 남은 물체를 센다
+    // 물체의 분류가 CHECK_INTERVAL 세대 전과 같고 모두 분류할 수 있으면 안정되었다고 본다
+    // 수프는 안정된 부분을 빨리 진행할 수 있는 HashLife로, 하나하나의 물체는 SparseField로 진행한다
+    pub fn search(rule: &Rule, soups: u64, seed: u64, density: f64) -> Self {
+        let mut census = Self {
+            soups,
+            generations: 0,
+            unsettled: 0,
+            unclassified: 0,
+            objects: HashMap::new(),
+            known: HashMap::new(),
+            splits: HashMap::new(),
+        };
+        for i in 0..soups {
+            let mut engine = HashLife::new();
+            generate(SOUP_SIZE, SOUP_SIZE, density, seed.wrapping_add(i)).transfer(
+                &mut engine,
+                0,
+                0,
+            );
+
+            let mut generation = 0;
+            let mut previous = None;
+            let codes = loop {
+                engine.advance(rule, CHECK_INTERVAL);
+                generation += CHECK_INTERVAL;
+                let last_check = generation >= MAX_GENERATIONS;
+
+                // 큰 물체일수록 아직 반응하고 있을 가능성이 높으므로 먼저 분류하고
+                // 분류할 수 없는 물체가 있으면 마지막 조사가 아닌 한 나머지는 조사하지 않는다
+                let mut found = census.split_objects(&engine.live_cells(), rule);
+                found.sort_by_key(|object| Reverse(object.len()));
+                let mut codes = Vec::new();
+                for object in &found {
+                    let code = census.code(object, rule);
+                    let failed = code.is_none();
+                    codes.push(code);
+                    if failed && !last_check {
+                        break;
+                    }
+                }
+                codes.sort_unstable();
+                let settled = codes.len() == found.len()
+                    && codes.iter().all(Option::is_some)
+                    && previous.as_ref() == Some(&codes);
+                if settled || last_check {
+                    if !settled {
+                        census.unsettled += 1;
+                    }
+                    break codes;
+                }
+                previous = Some(codes);
+            };
+
+            census.generations += generation;
+            for code in codes {
+                match code {
+                    Some(code) => *census.objects.entry(code).or_insert(0) += 1,
+                    None => census.unclassified += 1,
+                }
+            }
+        }
+        census
+    }
+
+    // 살아 있는 셀을 독립된 물체로 나눈다. 같은 모양의 덩어리를 이미 나눴으면 다시 진행시키지 않는다
+    fn split_objects(&mut self, cells: &[(i64, i64)], rule: &Rule) -> Vec<Vec<(i64, i64)>> {
+        let mut objects = Vec::new();
+        for cluster in clusters(cells, INTERACTION_DISTANCE) {
+            let Some((x0, y0, _, _)) = bounding_box(&cluster) else {
+                continue;
+            };
+            let parts = self
+                .splits
+                .entry(normalise(&cluster))
+                .or_insert_with_key(|shape| split(shape, rule));
+            objects.extend(
+                parts
+                    .iter()
+                    .map(|part| part.iter().map(|(x, y)| (x + x0, y + y0)).collect()),
+            );
+        }
+        objects
+    }
+
+    // 물체의 코드. 같은 모양을 이미 분류했으면 다시 진행시키지 않는다
+    fn code(&mut self, object: &[(i64, i64)], rule: &Rule) -> Option<String> {
+        let shape = normalise(object);
+        if let Some(code) = self.known.get(&shape) {
+            return Some(code.clone());
+        }
+        let code = classify(object, rule)?;
+        self.known.insert(shape, code.clone());
+        Some(code)
+    }
+
+    // 고정물, 진동자, 우주선으로 나누어 많은 순으로 나열한 표
+    pub fn report(&self) -> String {
+        let mut text = format!(
+            "수프 {}개  합계 {}세대  안정되지 않은 수프 {}개  분류할 수 없었던 물체 {}개\n",
+            self.soups, self.generations, self.unsettled, self.unclassified
+        );
+        for (prefix, title) in [("xs", "고정물"), ("xp", "진동자"), ("xq", "우주선")] {
+            let mut objects: Vec<_> = self
+                .objects
+                .iter()
+                .filter(|(code, _)| code.starts_with(prefix))
+                .collect();
+            objects.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+            let total: usize = objects.iter().map(|(_, count)| **count).sum();
+            text += &format!("\n{title} {}종류 {total}개\n", objects.len());
+            for (code, count) in objects {
+                let name = NAMES
+                    .iter()
+                    .find(|(known, _)| known == code)
+                    .map_or("", |(_, name)| name);
+                text += format!("{count:>10}  {code}  {name}").trim_end();
+                text.push('\n');
+            }
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn life() -> Rule {
+        Rule::parse("B3/S23").unwrap()
+    }
+
+    // cells를 물체로 나눠 각각의 코드를 정렬하여 반환한다
+    fn codes(cells: &[(i64, i64)]) -> Vec<String> {
+        let rule = life();
+        let mut census = Census::search(&rule, 0, 0, 0.0);
+        let objects = census.split_objects(cells, &rule);
+        let mut codes: Vec<_> = objects
+            .iter()
+            .map(|object| census.code(object, &rule).unwrap())
+            .collect();
+        codes.sort_unstable();
+        codes
+    }
+
+    fn count(census: &Census, code: &str) -> usize {
+        census.objects.get(code).copied().unwrap_or(0)
+    }
+
+    #[test]
+    fn neighbouring_still_lifes_are_counted_separately() {
+        // 한 칸 사이를 두고 나란히 놓인 블록 두 개
+        let blocks = [
+            (0, 0),
+            (1, 0),
+            (0, 1),
+            (1, 1),
+            (3, 0),
+            (4, 0),
+            (3, 1),
+            (4, 1),
+        ];
+        assert_eq!(codes(&blocks), ["xs4_33", "xs4_33"]);
+    }
+
+    #[test]
+    fn oscillators_with_separate_parts_stay_one_object() {
+        // 가운데의 두 셀이 없는 위상의 비컨은 8방향으로 이어져 있지 않다
+        let beacon = [(0, 0), (1, 0), (0, 1), (3, 2), (2, 3), (3, 3)];
+        assert_eq!(clusters(&beacon, 1).len(), 2);
+        assert_eq!(codes(&beacon), ["xp2_318c"]);
+
+        // 두꺼비도 한쪽의 위상에서는 두 부분으로 나뉘어 있다
+        let toad = [(2, 0), (0, 1), (3, 1), (0, 2), (3, 2), (1, 3)];
+        assert_eq!(clusters(&toad, 1).len(), 2);
+        assert_eq!(codes(&toad), ["xp2_7e"]);
+    }
+
+    #[test]
+    fn soups_are_fixed_by_seed() {
+        // 난수의 알고리즘이나 셀로 바꾸는 방법이 바뀌면 같은 seed의 수프가 달라진다
+        let soup = generate(16, 2, 0.5, 0);
+        assert_eq!(
+            soup.cells,
+            [
+                0, 0, 1, 1, 1, 1, 1, 1, 1, 0, 0, 1, 0, 1, 0, 0, //
+                0, 0, 0, 0, 1, 0, 1, 0, 0, 1, 0, 1, 1, 1, 0, 1,
+            ]
+        );
+        assert_eq!(
+            generate(16, 16, 0.35, 42).cells,
+            generate(16, 16, 0.35, 42).cells
+        );
+        assert_ne!(
+            generate(16, 16, 0.35, 42).cells,
+            generate(16, 16, 0.35, 43).cells
+        );
+        assert!(generate(4, 4, 0.0, 1).cells.iter().all(|cell| *cell == 0));
+        assert!(generate(4, 4, 1.0, 1).cells.iter().all(|cell| *cell == 1));
+    }
+
+    #[test]
+    fn census_of_fixed_seeds() {
+        let census = Census::search(&life(), 5, 5, 0.35);
+        assert_eq!(census.unsettled, 0);
+        assert_eq!(census.unclassified, 0);
+        assert_eq!(count(&census, "xs4_33"), 24);
+        assert_eq!(count(&census, "xp2_7"), 14);
+        assert_eq!(count(&census, "xq4_153"), 5);
+    }
+}
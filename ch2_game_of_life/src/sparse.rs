@@ -0,0 +1,117 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::engine::Engine;
+use crate::rule::Rule;
+
+// 살아 있는 셀의 좌표만 보유하는 끝이 없는 평면
+// 메모리 사용량은 필드의 크기가 아니라 살아 있는 셀의 수에 비례한다
+pub struct SparseField {
+    cells: HashSet<(i64, i64)>,
+}
+
+impl SparseField {
+    pub fn new() -> Self {
+        Self {
+            cells: HashSet::new(),
+        }
+    }
+}
+
+impl Engine for SparseField {
+    fn name(&self) -> String {
+        format!("sparse cells {}", self.cells.len())
+    }
+
+    fn step(&mut self, rule: &Rule) {
+        // 살아 있는 셀의 이웃에만 수를 더해 가면, 수가 0이 아닌 셀만 조사하면 된다
+        let mut counts: HashMap<(i64, i64), usize> = HashMap::new();
+        for (x, y) in &self.cells {
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    if (dx, dy) != (0, 0) {
+                        *counts.entry((x + dx, y + dy)).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+
+        // 이웃이 하나도 없는 살아 있는 셀도 판정한다
+        for cell in &self.cells {
+            counts.entry(*cell).or_insert(0);
+        }
+
+        self.cells = counts
+            .into_iter()
+            .filter(|(cell, count)| rule.next_state(self.cells.contains(cell), *count))
+            .map(|(cell, _)| cell)
+            .collect();
+    }
+
+    fn get(&self, x: i64, y: i64) -> bool {
+        self.cells.contains(&(x, y))
+    }
+
+    fn set(&mut self, x: i64, y: i64, alive: bool) {
+        if alive {
+            self.cells.insert((x, y));
+        } else {
+            self.cells.remove(&(x, y));
+        }
+    }
+
+    fn size(&self) -> Option<(usize, usize)> {
+        None
+    }
+
+    fn live_cells(&self) -> Vec<(i64, i64)> {
+        self.cells.iter().copied().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::{Field, Topology};
+    use crate::pattern::Pattern;
+
+    #[test]
+    fn matches_grid() {
+        for (name, rule, generations) in [
+            ("r-pentomino", "B3/S23", 100),
+            ("gosper-gun", "B3/S23", 90),
+            ("acorn", "B36/S23", 90),
+            ("pulsar", "B2/S", 12),
+        ] {
+            let rule = Rule::parse(rule).unwrap();
+            let pattern = Pattern::named(name).unwrap();
+            let mut field = Field::new(112, 112, Topology::Plane);
+            let mut sparse = SparseField::new();
+            pattern.transfer(&mut field, 40, 40);
+            pattern.transfer(&mut sparse, 40, 40);
+            for generation in 1..=generations {
+                field.step(&rule);
+                sparse.step(&rule);
+                let mut expected = field.live_cells();
+                let mut cells = sparse.live_cells();
+                expected.sort_unstable();
+                cells.sort_unstable();
+                assert_eq!(cells, expected, "{name} {rule} gen {generation}");
+            }
+        }
+    }
+
+    #[test]
+    fn glider_travels_without_edge() {
+        // 끝이 없으므로 글라이더는 원점에서 멀리 떨어져도 계속 날아간다
+        let rule = Rule::default();
+        let mut sparse = SparseField::new();
+        Pattern::named("glider")
+            .unwrap()
+            .transfer(&mut sparse, 0, 0);
+        let start = sparse.live_cells().len();
+        sparse.advance(&rule, 4000);
+        let cells = sparse.live_cells();
+        assert_eq!(cells.len(), start);
+        assert!(cells.iter().all(|(x, y)| *x >= 1000 && *y >= 1000));
+    }
+}
@@ -0,0 +1,215 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::engine::Engine;
+
+// 주기 검출을 위해 해시를 기억해 두는 기록의 수. 이보다 긴 주기는 검출하지 않는다
+const CYCLE_WINDOW: usize = 4096;
+
+// 살아 있는 셀을 모두 포함하는 가장 작은 직사각형(x, y, 폭, 높이)을 반환한다
+pub fn bounding_box(cells: &[(i64, i64)]) -> Option<(i64, i64, usize, usize)> {
+    let (&(x, y), rest) = cells.split_first()?;
+    let (x0, y0, x1, y1) = rest.iter().fold((x, y, x, y), |(x0, y0, x1, y1), (x, y)| {
+        (x0.min(*x), y0.min(*y), x1.max(*x), y1.max(*y))
+    });
+    Some((x0, y0, (x1 - x0 + 1) as usize, (y1 - y0 + 1) as usize))
+}
+
+// 셀 하나의 해시. 필드의 해시는 이것을 더한 것이므로 셀의 순서와 관계가 없다
+fn cell_hash(x: i64, y: i64, state: u8) -> u64 {
+    // splitmix64의 섞는 함수로 좌표와 상태를 고르게 흩뜨린다
+    let mut z = (x as u64)
+        .wrapping_mul(0x9e37_79b9_7f4a_7c15)
+        .wrapping_add((y as u64).wrapping_mul(0xc2b2_ae3d_27d4_eb4f))
+        .wrapping_add(state as u64);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+// 필드가 같은 상태로 돌아온 주기
+#[derive(Clone, Copy)]
+pub struct Cycle {
+    pub start: u64,  // 처음으로 그 상태가 된 세대
+    pub period: u64, // 주기. 1이면 고정물
+}
+
+// 세대마다의 통계와 주기 검출
+pub struct Stats {
+    pub population: usize,
+    pub births: usize, // 직전에 기록한 세대부터 태어난 셀의 수
+    pub deaths: usize, // 직전에 기록한 세대부터 죽은 셀의 수
+    pub bounding_box: Option<(i64, i64, usize, usize)>,
+    pub cycle: Option<Cycle>,
+    previous: Option<HashSet<(i64, i64)>>, // 직전에 기록한 세대의 살아 있는 셀
+    seen: HashMap<u64, u64>,               // 최근 CYCLE_WINDOW개의 필드의 해시 → 그 상태였던 세대
+    recent: VecDeque<u64>,                 // seen에 넣은 해시를 오래된 순으로
+}
+
+impl Stats {
+    pub fn new() -> Self {
+        Self {
+            population: 0,
+            births: 0,
+            deaths: 0,
+            bounding_box: None,
+            cycle: None,
+            previous: None,
+            seen: HashMap::new(),
+            recent: VecDeque::new(),
+        }
+    }
+
+    // 편집 등으로 이력이 끊겼을 때 주기 검출을 처음부터 다시 한다
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    // generation 세대의 엔진의 상태를 기록한다
+    pub fn record(&mut self, generation: u64, engine: &dyn Engine) {
+        // 엔진마다 셀의 순서가 다르므로 정렬하지 않아도 되도록 셀의 해시를 더한다
+        // Generations 규칙에서는 죽어 가는 셀도 상태의 일부이다
+        let live_cells = engine.live_cells();
+        let hash = live_cells
+            .iter()
+            .map(|(x, y)| cell_hash(*x, *y, 1))
+            .chain(
+                engine
+                    .dying_cells()
+                    .into_iter()
+                    .map(|(x, y, state)| cell_hash(x, y, state)),
+            )
+            .fold(0, u64::wrapping_add);
+
+        let current: HashSet<(i64, i64)> = live_cells.iter().copied().collect();
+        (self.births, self.deaths) = match &self.previous {
+            Some(previous) => (
+                current.difference(previous).count(),
+                previous.difference(&current).count(),
+            ),
+            None => (0, 0),
+        };
+        self.population = live_cells.len();
+        self.bounding_box = bounding_box(&live_cells);
+        self.previous = Some(current);
+
+        if self.cycle.is_none() {
+            match self.seen.get(&hash) {
+                Some(start) if *start < generation => {
+                    self.cycle = Some(Cycle {
+                        start: *start,
+                        period: generation - start,
+                    });
+                }
+                Some(_) => {}
+                None => {
+                    self.seen.insert(hash, generation);
+                    self.recent.push_back(hash);
+                    // 오래된 기록을 버려 긴 실행에서도 메모리를 일정하게 한다
+                    if self.recent.len() > CYCLE_WINDOW
+                        && let Some(oldest) = self.recent.pop_front()
+                    {
+                        self.seen.remove(&oldest);
+                    }
+                }
+            }
+        }
+    }
+
+    // 상태 행에 표시하는 문자열
+    pub fn summary(&self) -> String {
+        let mut text = format!("pop {}  +{} -{}", self.population, self.births, self.deaths);
+        if let Some((x, y, width, height)) = self.bounding_box {
+            text += &format!("  bbox {width}x{height} at ({x}, {y})");
+        }
+        match self.cycle {
+            Some(Cycle { start, .. }) if self.population == 0 => {
+                text += &format!("  gen {start}에 전멸")
+            }
+            Some(Cycle { start, period: 1 }) => text += &format!("  gen {start}부터 고정"),
+            Some(Cycle { start, period }) => text += &format!("  gen {start}부터 주기 {period}"),
+            None => {}
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::{Field, Topology};
+    use crate::pattern::Pattern;
+    use crate::rule::Rule;
+    use crate::sparse::SparseField;
+
+    // rle의 패턴을 주기가 나타날 때까지 진행시킨다
+    fn run(rle: &str, rule: &str, max_generations: u64) -> Stats {
+        let rule = Rule::parse(rule).unwrap();
+        let mut field = Field::new(32, 32, Topology::Torus);
+        Pattern::parse_rle(rle)
+            .unwrap()
+            .transfer(&mut field, 10, 10);
+        let mut stats = Stats::new();
+        stats.record(0, &field);
+        for generation in 1..=max_generations {
+            if stats.cycle.is_some() {
+                break;
+            }
+            field.step(&rule);
+            stats.record(generation, &field);
+        }
+        stats
+    }
+
+    #[test]
+    fn block_is_still() {
+        let stats = run("x = 2, y = 2\n2o$2o!", "B3/S23", 10);
+        let cycle = stats.cycle.unwrap();
+        assert_eq!((cycle.start, cycle.period), (0, 1));
+        assert_eq!(stats.population, 4);
+        assert_eq!((stats.births, stats.deaths), (0, 0));
+        assert_eq!(stats.bounding_box, Some((10, 10, 2, 2)));
+        assert!(stats.summary().ends_with("gen 0부터 고정"));
+    }
+
+    #[test]
+    fn blinker_has_period_two() {
+        let stats = run("x = 3, y = 1\n3o!", "B3/S23", 10);
+        let cycle = stats.cycle.unwrap();
+        assert_eq!((cycle.start, cycle.period), (0, 2));
+        assert_eq!(stats.population, 3);
+        assert_eq!((stats.births, stats.deaths), (2, 2));
+        assert_eq!(stats.bounding_box, Some((10, 10, 3, 1)));
+    }
+
+    #[test]
+    fn cycle_after_transient() {
+        // R 펜토미노의 조상인 3셀은 1세대 뒤에 블록이 되어 고정된다
+        let stats = run("x = 2, y = 2\n2o$o!", "B3/S23", 10);
+        let cycle = stats.cycle.unwrap();
+        assert_eq!((cycle.start, cycle.period), (1, 1));
+
+        // Brian's Brain에서는 죽어 가는 셀도 상태에 포함되므로 죽어 가는 셀이 사라진 세대부터 고정이다
+        let stats = run("x = 1, y = 1\no!", "B2/S/C3", 10);
+        let cycle = stats.cycle.unwrap();
+        assert_eq!(stats.population, 0);
+        assert_eq!((cycle.start, cycle.period), (2, 1));
+    }
+
+    #[test]
+    fn seen_table_is_bounded() {
+        // 글라이더는 같은 상태로 돌아오지 않으므로 기록은 CYCLE_WINDOW개에서 멈춘다
+        let rule = Rule::default();
+        let mut sparse = SparseField::new();
+        Pattern::named("glider")
+            .unwrap()
+            .transfer(&mut sparse, 0, 0);
+        let mut stats = Stats::new();
+        for generation in 0..CYCLE_WINDOW as u64 * 2 {
+            stats.record(generation, &sparse);
+            sparse.step(&rule);
+        }
+        assert!(stats.cycle.is_none());
+        assert_eq!(stats.seen.len(), CYCLE_WINDOW);
+        assert_eq!(stats.recent.len(), CYCLE_WINDOW);
+    }
+}
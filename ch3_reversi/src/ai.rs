@@ -0,0 +1,331 @@
+use std::time::{Duration, Instant};
+
+use rand::{rngs::ThreadRng, seq::IndexedRandom, seq::SliceRandom};
+
+use crate::bitboard::{self, BitBoard};
+use crate::board::Board;
+use crate::book::Book;
+use crate::{BOARD_HEIGHT, BOARD_WIDTH, LevelEnum, TurnEnum, Vec2};
+
+// 칸마다의 위치의 가치. 모서리는 높고, 모서리를 내주기 쉬운 그 옆의 칸은 낮다
+#[rustfmt::skip]
+const WEIGHTS: [i32; BOARD_HEIGHT * BOARD_WIDTH] = [
+    100, -20,  10,   5,   5,  10, -20, 100,
+    -20, -50,  -2,  -2,  -2,  -2, -50, -20,
+     10,  -2,   1,   1,   1,   1,  -2,  10,
+      5,  -2,   1,   0,   0,   1,  -2,   5,
+      5,  -2,   1,   0,   0,   1,  -2,   5,
+     10,  -2,   1,   1,   1,   1,  -2,  10,
+    -20, -50,  -2,  -2,  -2,  -2, -50, -20,
+    100, -20,  10,   5,   5,  10, -20, 100,
+];
+
+// 놓을 수 있는 칸의 수(착수 가능 수)의 차 하나당 점수
+const MOBILITY_WEIGHT: i32 = 5;
+
+// 승부가 난 판의 점수. 이긴 쪽은 여기에 돌의 차를 더한다
+const WIN_SCORE: i32 = 100_000;
+const INFINITY: i32 = 1_000_000;
+
+// 시간을 확인하는 간격(노드 수)
+const CLOCK_INTERVAL: u64 = 1024;
+
+// 강함마다 읽는 최대의 깊이와 한 수에 쓰는 시간
+fn get_limits(level: LevelEnum) -> (u32, Duration) {
+    match level {
+        LevelEnum::Easy => (2, Duration::from_millis(100)),
+        LevelEnum::Normal => (4, Duration::from_millis(500)),
+        _ => (
+            (BOARD_WIDTH * BOARD_HEIGHT) as u32,
+            Duration::from_millis(2000),
+        ),
+    }
+}
+
+// 컴퓨터가 고른 수와 그 근거
+pub struct SearchResult {
+    pub position: Vec2,
+    pub score: i32,      // 고른 쪽에서 본 평가값
+    pub depth: u32,      // 끝까지 읽은 깊이. 랜덤이나 정석이면 0
+    pub nodes: u64,      // 조사한 국면의 수
+    pub from_book: bool, // 정석에서 고른 수인지 여부
+}
+
+// 알파-베타 탐색의 상태
+struct Searcher {
+    deadline: Instant,
+    nodes: u64,
+    aborted: bool, // 시간이 다 되어 도중에 그만두었는지 여부
+}
+
+impl Searcher {
+    // color에서 본 판의 평가값. 위치의 가치와 착수 가능 수의 차를 더한다
+    fn evaluate(board: &BitBoard, color: TurnEnum, mobility: u32) -> i32 {
+        let opponent = color.get_opponent();
+        let weight = |color| -> i32 {
+            bitboard::squares(board.get_own(color))
+                .map(|square| WEIGHTS[square as usize])
+                .sum()
+        };
+        let opponent_mobility = board.get_legal_moves(opponent).count_ones();
+        weight(color) - weight(opponent)
+            + MOBILITY_WEIGHT * (mobility as i32 - opponent_mobility as i32)
+    }
+
+    // 승부가 난 판의 color에서 본 점수
+    fn evaluate_end(board: &BitBoard, color: TurnEnum) -> i32 {
+        let difference =
+            board.get_disk_count(color) as i32 - board.get_disk_count(color.get_opponent()) as i32;
+        match difference {
+            0 => 0,
+            _ if difference > 0 => WIN_SCORE + difference,
+            _ => -WIN_SCORE + difference,
+        }
+    }
+
+    // 네가맥스 형식의 알파-베타 탐색. color에서 본 평가값을 반환한다
+    // passed는 직전의 수가 패스였는지 여부로, 두 번 이어서 패스하면 승부가 난 것이다
+    fn search(
+        &mut self,
+        board: &BitBoard,
+        color: TurnEnum,
+        depth: u32,
+        mut alpha: i32,
+        beta: i32,
+        passed: bool,
+    ) -> i32 {
+        self.nodes += 1;
+        if self.nodes.is_multiple_of(CLOCK_INTERVAL) && Instant::now() >= self.deadline {
+            self.aborted = true;
+        }
+        if self.aborted {
+            return 0;
+        }
+
+        let moves = board.get_legal_moves(color);
+        if moves == 0 {
+            if passed {
+                return Self::evaluate_end(board, color);
+            }
+            return -self.search(board, color.get_opponent(), depth, -beta, -alpha, true);
+        }
+        if depth == 0 {
+            return Self::evaluate(board, color, moves.count_ones());
+        }
+
+        for square in bitboard::squares(moves) {
+            let mut next = *board;
+            next.place(color, square);
+            let score = -self.search(&next, color.get_opponent(), depth - 1, -beta, -alpha, false);
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+        alpha
+    }
+}
+
+// color가 놓을 수를 level의 강함으로 고른다. 놓을 수 있는 칸이 없으면 None을 반환한다
+// 랜덤 이외는 깊이 1부터 한 단계씩 깊게 읽어 가며(반복 심화) 시간이 다 되면
+// 마지막까지 읽은 깊이에서 가장 좋았던 수를 고른다. time을 지정하면 강함의 기본값 대신 쓴다
+// 정석이 있으면 랜덤 이외는 정석에 있는 국면인 동안 정석의 수를 둔다
+pub fn choose_move(
+    board: &Board,
+    color: TurnEnum,
+    level: LevelEnum,
+    time: Option<Duration>,
+    book: Option<&Book>,
+    rng: &mut ThreadRng,
+) -> Option<SearchResult> {
+    // 수를 읽는 것은 빠른 BitBoard로 한다
+    let board = BitBoard::from_board(board);
+    let mut moves: Vec<Vec2> = bitboard::squares(board.get_legal_moves(color))
+        .map(bitboard::to_position)
+        .collect();
+
+    if level == LevelEnum::Random {
+        // [6-9-28]놓을 수 있는 곳을 랜덤으로 얻는다
+        return moves.choose(rng).map(|position| SearchResult {
+            position: *position,
+            score: 0,
+            depth: 0,
+            nodes: 0,
+            from_book: false,
+        });
+    }
+
+    if let Some(position) = book.and_then(|book| book.choose(&board, color, rng)) {
+        return Some(SearchResult {
+            position,
+            score: 0,
+            depth: 0,
+            nodes: 0,
+            from_book: true,
+        });
+    }
+
+    // 같은 평가의 수가 여럿이면 매번 같은 수를 두지 않도록 섞어 둔다
+    moves.shuffle(rng);
+    let (max_depth, budget) = get_limits(level);
+    let budget = time.unwrap_or(budget);
+    let mut searcher = Searcher {
+        deadline: Instant::now() + budget,
+        nodes: 0,
+        aborted: false,
+    };
+    let empties = board.get_disk_count(TurnEnum::None);
+    let mut result = SearchResult {
+        position: *moves.first()?,
+        score: 0,
+        depth: 0,
+        nodes: 0,
+        from_book: false,
+    };
+
+    for depth in 1..=max_depth {
+        let mut alpha = -INFINITY;
+        let mut best = 0;
+        for (i, position) in moves.iter().enumerate() {
+            let mut next = board;
+            next.place(color, bitboard::to_square(*position));
+            let score = -searcher.search(
+                &next,
+                color.get_opponent(),
+                depth - 1,
+                -INFINITY,
+                -alpha,
+                false,
+            );
+            if searcher.aborted {
+                break;
+            }
+            if score > alpha {
+                alpha = score;
+                best = i;
+            }
+        }
+        if searcher.aborted {
+            break;
+        }
+
+        // 가장 좋았던 수를 다음 깊이에서 먼저 읽어 가지치기가 잘 되게 한다
+        moves[..=best].rotate_right(1);
+        result.position = moves[0];
+        result.score = alpha;
+        result.depth = depth;
+
+        // 끝까지 읽었거나 승패가 확정되었으면 더 깊게 읽을 필요가 없다
+        if depth >= empties || alpha.abs() >= WIN_SCORE {
+            break;
+        }
+    }
+    result.nodes = searcher.nodes;
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FixedResources;
+
+    const LEVELS: [LevelEnum; 4] = [
+        LevelEnum::Random,
+        LevelEnum::Easy,
+        LevelEnum::Normal,
+        LevelEnum::Hard,
+    ];
+
+    // 짧은 시간으로 고르게 해 테스트를 빠르게 한다
+    fn choose(board: &Board, color: TurnEnum, level: LevelEnum) -> Option<SearchResult> {
+        let time = Some(Duration::from_millis(50));
+        choose_move(board, color, level, time, None, &mut rand::rng())
+    }
+
+    #[test]
+    fn every_level_chooses_a_legal_move() {
+        let mut board = Board::initial();
+        let mut color = TurnEnum::Black;
+        for _ in 0..6 {
+            for level in LEVELS {
+                let result = choose(&board, color, level).unwrap();
+                assert!(board.check_can_place(color, result.position, false));
+            }
+            let result = choose(&board, color, LevelEnum::Random).unwrap();
+            board.place(color, result.position);
+            color = color.get_opponent();
+        }
+    }
+
+    #[test]
+    fn no_move_only_without_legal_moves() {
+        // 흑은 놓을 수 있는 칸이 없고 백은 있다
+        let mut board = Board::parse(&format!("------XO{}", "-".repeat(56))).unwrap();
+        for level in LEVELS {
+            assert!(choose(&board, TurnEnum::Black, level).is_none());
+            let result = choose(&board, TurnEnum::White, level).unwrap();
+            assert!(board.check_can_place(TurnEnum::White, result.position, false));
+        }
+    }
+
+    #[test]
+    fn take_the_corner() {
+        // 흑은 a1의 모서리를 잡을 수 있다
+        let board = Board::parse(&format!(
+            "-OX-----{}---OX------XO---{}",
+            "-".repeat(16),
+            "-".repeat(24)
+        ))
+        .unwrap();
+        for level in [LevelEnum::Easy, LevelEnum::Normal] {
+            for _ in 0..10 {
+                let result = choose(&board, TurnEnum::Black, level).unwrap();
+                assert_eq!((result.position.x, result.position.y), (0, 0));
+            }
+        }
+    }
+
+    #[test]
+    fn take_the_winning_endgame_move() {
+        // 빈칸은 b1과 f8뿐이다. 흑은 b1이면 12개 차로 이기고 f8이면 4개 차로 진다
+        let board =
+            Board::parse("O-OOOOXOXXOXXXXOXXOXXOXOXXXXOXOOXXXOOOXOXXOOOOXXXXXXXXXXOOOOO-OX")
+                .unwrap();
+        for level in [LevelEnum::Easy, LevelEnum::Normal] {
+            for _ in 0..10 {
+                let result = choose(&board, TurnEnum::Black, level).unwrap();
+                assert_eq!((result.position.x, result.position.y), (1, 0));
+                assert_eq!(result.score, WIN_SCORE + 12);
+            }
+        }
+    }
+
+    #[test]
+    fn hard_stops_within_the_time() {
+        let time = Duration::from_millis(200);
+        let start = Instant::now();
+        let result = choose_move(
+            &Board::initial(),
+            TurnEnum::Black,
+            LevelEnum::Hard,
+            Some(time),
+            None,
+            &mut rand::rng(),
+        )
+        .unwrap();
+        assert!(start.elapsed() < time + Duration::from_millis(300));
+        assert!(result.depth > 0);
+    }
+
+    #[test]
+    fn random_is_the_easiest_level() {
+        assert_eq!(LevelEnum::Random as usize, 0);
+        let mut level = LevelEnum::Easy;
+        level.decrease();
+        assert!(level == LevelEnum::Random);
+        level.decrease();
+        assert!(level == LevelEnum::Hard);
+        assert!(LevelEnum::parse("random").unwrap() == LevelEnum::Random);
+        assert_eq!(FixedResources::new().level_names[0], "RANDOM");
+    }
+}
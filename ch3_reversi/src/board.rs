@@ -0,0 +1,239 @@
+use std::fmt;
+
+use crate::{BOARD_HEIGHT, BOARD_WIDTH, DirectionEnum, TurnEnum, Vec2};
+
+// [5-4]방향을 선언한다
+pub const DIRECTIONS: [(DirectionEnum, Vec2); DirectionEnum::Max as usize] = [
+    (DirectionEnum::Up, Vec2 { x: 0, y: -1 }),
+    (DirectionEnum::UpLeft, Vec2 { x: -1, y: -1 }),
+    (DirectionEnum::Left, Vec2 { x: -1, y: 0 }),
+    (DirectionEnum::DownLeft, Vec2 { x: -1, y: 1 }),
+    (DirectionEnum::Down, Vec2 { x: 0, y: 1 }),
+    (DirectionEnum::DownRight, Vec2 { x: 1, y: 1 }),
+    (DirectionEnum::Right, Vec2 { x: 1, y: 0 }),
+    (DirectionEnum::UpRight, Vec2 { x: 1, y: -1 }),
+];
+
+// 모눈판 각 칸의 상태. AI가 수를 읽을 때 복사해서 쓸 수 있도록 Copy로 한다
+#[derive(Clone, Copy)]
+pub struct Board {
+    cells: [TurnEnum; BOARD_HEIGHT * BOARD_WIDTH],
+}
+
+impl Board {
+    // 돌이 하나도 놓여 있지 않은 모눈판
+    pub fn new() -> Self {
+        Self {
+            cells: [TurnEnum::None; BOARD_HEIGHT * BOARD_WIDTH],
+        }
+    }
+
+    pub fn get(&self, x: i8, y: i8) -> TurnEnum {
+        self.cells[y as usize * BOARD_WIDTH + x as usize]
+    }
+
+    pub fn set(&mut self, x: i8, y: i8, color: TurnEnum) {
+        self.cells[y as usize * BOARD_WIDTH + x as usize] = color;
+    }
+
+    // 게임을 시작할 때의 모눈판
+    pub fn initial() -> Self {
+        let mut board = Self::new();
+
+        // [6-7-4]모눈판 중앙의 오른쪽 위와 왼쪽 아래에 검은 돌을 놓는다
+        board.set(3, 4, TurnEnum::Black);
+        board.set(4, 3, TurnEnum::Black);
+
+        // [6-7-5]모눈판 중앙의 왼쪽 위와 오른쪽 아래에 흰 돌을 놓는다
+        board.set(3, 3, TurnEnum::White);
+        board.set(4, 4, TurnEnum::White);
+        board
+    }
+
+    // a1, b1, ..., h8의 순으로 X(검은 돌) O(흰 돌) -(빈칸)을 늘어놓은 64문자를 모눈판으로 바꾼다
+    pub fn parse(text: &str) -> Result<Self, String> {
+        if text.chars().count() != BOARD_WIDTH * BOARD_HEIGHT {
+            return Err(format!(
+                "모눈판은 {}문자로 써야 합니다: {text}",
+                BOARD_WIDTH * BOARD_HEIGHT
+            ));
+        }
+        let mut board = Self::new();
+        for (i, c) in text.chars().enumerate() {
+            let color = match c {
+                'X' | 'x' | '*' => TurnEnum::Black,
+                'O' | 'o' => TurnEnum::White,
+                '-' | '.' => TurnEnum::None,
+                _ => return Err(format!("모눈판에 알 수 없는 문자 '{c}'이(가) 있습니다")),
+            };
+            board.set((i % BOARD_WIDTH) as i8, (i / BOARD_WIDTH) as i8, color);
+        }
+        Ok(board)
+    }
+
+    // [6-2]돌을 놓을 수 있는지 여부의 판정, 또는 돌을 뒤집는 함수를 선언한다
+    pub fn check_can_place(&mut self, color: TurnEnum, position: Vec2, turn_over: bool) -> bool {
+        let mut can_place = false; // [6-2-1]돌을 놓을 수 있는지 여부의 플래그를 선언한다
+
+        // [6-2-2]대상 좌표에 돌이 놓여 있지 않은지 여부를 판정한다
+        if self.get(position.x, position.y) != TurnEnum::None {
+            return false; // [6-2-3]돌이 놓여 있으면 놓을 수 없다는 결과를 반환한다
+        }
+
+        // [6-2-4]상대의 돌 색을 선언한다
+        let opponent = color.get_opponent();
+
+        // [6-2-5]모든 방향을 반복한다
+        for (_, direction) in &DIRECTIONS {
+            // [6-2-6]현재 체크 중인 좌표를 선언한다
+            let mut current_position = position;
+
+            // [6-2-7]옆의 칸으로 이동한다
+            current_position.add(direction);
+
+            // [6-2-7.1]체크하는 칸이 모눈판의 범위 내인지 판정한다
+            if !current_position.is_valid() {
+                // [6-2-7.2]대상 방향의 체크를 스킵한다
+                continue;
+            }
+
+            // [6-2-8]상대의 돌이 아닌지 판정한다
+            if self.get(current_position.x, current_position.y) != opponent {
+                // [6-2-9]상대의 돌이 아니면 그 방향의 체크를 중지한다
+                continue;
+            }
+
+            // [6-2-10]무한 루프한다
+            loop {
+                // [6-2-11]옆 칸으로 이동한다
+                current_position.add(direction);
+
+                // [6-2-12]체크하는 칸이 모눈판의 범위 내인지 판정한다
+                if !current_position.is_valid() {
+                    // [6-2-13]모눈판 바깥쪽으로 나가면 현재 방향의 체크를 빠져나간다
+                    break;
+                }
+
+                // [6-2-14]체크하는 칸에 돌이 있는지 여부를 판정한다
+                if self.get(current_position.x, current_position.y) == TurnEnum::None {
+                    break; // [6-2-15]돌이 없으면 현재 방향의 체크를 빠져나간다
+                }
+
+                // [6-2-16]체크하는 칸에 자신의 돌이 있으면
+                if self.get(current_position.x, current_position.y) == color {
+                    // [6-2-17]돌을 놓을 수 있는 것이 확정된다
+                    can_place = true;
+
+                    // [6-2-18]뒤집기 플래그가 설정되어 있는지 여부를 판정한다
+                    if turn_over {
+                        // [6-2-19]뒤집는 좌표를 선언한다
+                        let mut reverse_position = position;
+
+                        // [6-2-20]옆 칸으로 이동한다
+                        reverse_position.add(direction);
+
+                        // [6-2-21]현재 턴의 돌을 찾을 때까지 반복한다
+                        loop {
+                            // [6-2-22]상대의 돌을 뒤집는다
+                            self.set(reverse_position.x, reverse_position.y, color);
+
+                            // [6-2-23]옆 칸으로 이동한다
+                            reverse_position.add(direction);
+
+                            // 크래시 막기
+                            if !reverse_position.is_valid() {
+                                break;
+                            }
+
+                            if self.get(reverse_position.x, reverse_position.y) == color {
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        can_place // [6-2-24]돌을 놓을 수 있는지 여부를 반환한다
+    }
+
+    // [6-3]모눈판 위에 돌을 놓을 수 있는 칸이 있는지 여부를 판정하는 함수를 선언한다
+    pub fn check_can_place_all(&mut self, color: TurnEnum) -> bool {
+        for y in 0..BOARD_HEIGHT {
+            for x in 0..BOARD_WIDTH {
+                // [6-3-3]판정하는 좌표를 선언한다
+                let position = Vec2 {
+                    x: x as i8,
+                    y: y as i8,
+                };
+
+                // [6-3-4]대상 좌표에 돌을 놓을 수 있는지 여부를 판정한다
+                if self.check_can_place(color, position, false) {
+                    return true; // [6-3-5]돌을 놓을 수 있는 칸이 있다는 결과를 반환한다
+                }
+            }
+        }
+        false // [6-3-6]돌을 놓을 수 있는 칸이 없다는 결과를 반환한다
+    }
+
+    // [6-4]임의의 돌의 개수를 세는 함수를 선언한다
+    pub fn get_disk_count(&self, color: TurnEnum) -> u32 {
+        let mut count = 0; // [6-4-1]세는 돌의 개수를 보유하는 변수를 선언한다
+
+        for y in 0..BOARD_HEIGHT {
+            for x in 0..BOARD_WIDTH {
+                // [6-4-4]대상 칸에 대상의 돌이 있는지 여부를 판정한다
+                if self.get(x as i8, y as i8) == color {
+                    count += 1; // [6-4-5]돌의 개수를 더한다
+                }
+            }
+        }
+        count // [6-4-6]센 돌의 개수를 반환한다
+    }
+
+    // color가 돌을 놓을 수 있는 좌표를 모두 반환한다
+    pub fn get_legal_moves(&mut self, color: TurnEnum) -> Vec<Vec2> {
+        // [6-9-22]놓을 수 있는 좌표를 보유하는 벡터를 선언한다
+        let mut positions = Vec::new();
+
+        for y in 0..BOARD_HEIGHT {
+            for x in 0..BOARD_WIDTH {
+                // [6-9-25]대상 칸의 좌표를 선언한다
+                let position = Vec2 {
+                    x: x as i8,
+                    y: y as i8,
+                };
+
+                // [6-9-26]대상 좌표에 돌을 놓을 수 있는지 여부를 판정한다
+                if self.check_can_place(color, position, false) {
+                    // [6-9-27]벡터에 대상 좌표를 추가한다
+                    positions.push(position);
+                }
+            }
+        }
+        positions
+    }
+
+    // position에 color의 돌을 놓고 사이에 낀 상대의 돌을 뒤집는다
+    pub fn place(&mut self, color: TurnEnum, position: Vec2) {
+        // [6-9-29]돌을 뒤집는다
+        self.check_can_place(color, position, true);
+
+        // [6-9-30]현재 턴의 돌을 놓는다
+        self.set(position.x, position.y, color);
+    }
+}
+
+// parse가 읽을 수 있는 64문자로 나타낸다
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for cell in &self.cells {
+            let c = match cell {
+                TurnEnum::Black => 'X',
+                TurnEnum::White => 'O',
+                _ => '-',
+            };
+            write!(f, "{c}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+use std::fs;
+
+use rand::rngs::ThreadRng;
+use rand::seq::IndexedRandom;
+
+use crate::bitboard::{self, BitBoard};
+use crate::board::Board;
+use crate::record::{self, Record};
+use crate::{BOARD_HEIGHT, BOARD_WIDTH, TurnEnum, Vec2};
+
+// 모눈판의 대칭의 수. 회전과 뒤집기를 조합한 8가지
+const SYMMETRIES: u32 = 8;
+
+// 좌표에 symmetry번째의 대칭 변환을 적용한다
+// 비트 0은 좌우 뒤집기, 비트 1은 상하 뒤집기, 비트 2는 대각선(a1-h8) 뒤집기로, 이 순으로 적용한다
+fn transform(symmetry: u32, position: Vec2) -> Vec2 {
+    let mut position = position;
+    if symmetry & 1 != 0 {
+        position.x = BOARD_WIDTH as i8 - 1 - position.x;
+    }
+    if symmetry & 2 != 0 {
+        position.y = BOARD_HEIGHT as i8 - 1 - position.y;
+    }
+    if symmetry & 4 != 0 {
+        (position.x, position.y) = (position.y, position.x);
+    }
+    position
+}
+
+// transform의 역변환. 뒤집기를 반대의 순서로 적용한다
+fn inverse_transform(symmetry: u32, position: Vec2) -> Vec2 {
+    let mut position = position;
+    if symmetry & 4 != 0 {
+        (position.x, position.y) = (position.y, position.x);
+    }
+    transform(symmetry & 3, position)
+}
+
+fn transform_bits(symmetry: u32, bits: u64) -> u64 {
+    bitboard::squares(bits)
+        .map(|square| transform(symmetry, bitboard::to_position(square)))
+        .fold(0, |bits, position| {
+            bits | 1 << bitboard::to_square(position)
+        })
+}
+
+// 정석에서 국면을 찾는 열쇠. 검은 돌, 흰 돌, 검은 돌이 둘 차례인지 여부
+type Key = (u64, u64, bool);
+
+// symmetry번째의 대칭 변환을 적용한 국면의 열쇠
+fn get_key(board: &BitBoard, color: TurnEnum, symmetry: u32) -> Key {
+    (
+        transform_bits(symmetry, board.black),
+        transform_bits(symmetry, board.white),
+        color == TurnEnum::Black,
+    )
+}
+
+// 국면의 8가지 대칭 중 열쇠가 가장 작은 것을 대표로 하여 그 열쇠와 대칭의 번호를 반환한다
+fn normalise(board: &BitBoard, color: TurnEnum) -> (Key, u32) {
+    (0..SYMMETRIES)
+        .map(|symmetry| (get_key(board, color, symmetry), symmetry))
+        .min()
+        .unwrap()
+}
+
+// 정석. 국면마다 둘 수의 후보와 고르는 비율(무게)을 가진다
+// 파일에는 한 행에 한 국면을 다음과 같이 쓴다. '#'으로 시작하는 행과 빈 행은 무시한다
+//   모눈판 차례 수:무게 수:무게 ...
+//   모눈판  a1, b1, ..., h8의 순으로 X(검은 돌) O(흰 돌) -(빈칸)을 늘어놓은 64문자
+//   차례    둘 차례인 쪽. X 또는 O
+//   수:무게 「f5:10」처럼 둘 수와 무게. 무게가 클수록 자주 고른다
+// 회전하거나 뒤집어서 겹치는 국면은 같은 국면으로 보므로 어느 하나만 쓰면 된다
+pub struct Book {
+    positions: HashMap<Key, Vec<(Vec2, u32)>>, // 대표의 대칭으로 바꾼 국면과 수
+}
+
+impl Book {
+    pub fn new() -> Self {
+        Self {
+            positions: HashMap::new(),
+        }
+    }
+
+    // board에서 color가 position에 두는 수의 무게를 weight만큼 늘린다
+    fn add(&mut self, board: &BitBoard, color: TurnEnum, position: Vec2, weight: u32) {
+        let (key, _) = normalise(board, color);
+        // 처음의 국면처럼 국면 자신이 대칭이면 겹치는 수들도 같은 수로 본다
+        let position = (0..SYMMETRIES)
+            .filter(|symmetry| get_key(board, color, *symmetry) == key)
+            .map(|symmetry| transform(symmetry, position))
+            .min_by_key(|position| bitboard::to_square(*position))
+            .unwrap();
+        let moves = self.positions.entry(key).or_default();
+        match moves
+            .iter_mut()
+            .find(|(p, _)| p.x == position.x && p.y == position.y)
+        {
+            Some((_, w)) => *w += weight,
+            None => moves.push((position, weight)),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut book = Self::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |message: String| format!("{}행: {message}", i + 1);
+            let mut words = line.split_whitespace();
+            let board = Board::parse(words.next().unwrap_or_default()).map_err(error)?;
+            let color = match words.next() {
+                Some("X" | "x") => TurnEnum::Black,
+                Some("O" | "o") => TurnEnum::White,
+                _ => return Err(error("차례는 X 또는 O로 써야 합니다".to_string())),
+            };
+            let bitboard = BitBoard::from_board(&board);
+            let legal_moves = bitboard.get_legal_moves(color);
+            for word in words {
+                let (text, weight) = word.split_once(':').unwrap_or((word, "1"));
+                let position = record::parse_position(text)
+                    .ok_or_else(|| error(format!("수를 해석할 수 없습니다: {word}")))?;
+                let weight = weight
+                    .parse()
+                    .map_err(|_| error(format!("무게가 잘못되었습니다: {word}")))?;
+                if legal_moves >> bitboard::to_square(position) & 1 == 0 {
+                    return Err(error(format!("{position}에는 놓을 수 없습니다")));
+                }
+                book.add(&bitboard, color, position, weight);
+            }
+        }
+        Ok(book)
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text =
+            fs::read_to_string(path).map_err(|e| format!("{path}을(를) 읽을 수 없습니다: {e}"))?;
+        Self::parse(&text).map_err(|e| format!("{path}: {e}"))
+    }
+
+    // 파일의 형식으로 바꾼다. 돌이 적은 국면부터, 무게가 큰 수부터 쓴다
+    pub fn to_text(&self) -> String {
+        let mut positions: Vec<_> = self.positions.iter().collect();
+        positions.sort_by_key(|((black, white, is_black), _)| {
+            ((black | white).count_ones(), *black, *white, !is_black)
+        });
+
+        let mut text =
+            String::from("# 모눈판(a1~h8, X 검은 돌, O 흰 돌, - 빈칸) 차례 수:무게 ...\n");
+        for ((black, white, is_black), moves) in positions {
+            let mut board = Board::new();
+            for (bits, color) in [(black, TurnEnum::Black), (white, TurnEnum::White)] {
+                for position in bitboard::squares(*bits).map(bitboard::to_position) {
+                    board.set(position.x, position.y, color);
+                }
+            }
+            let mut moves = moves.clone();
+            moves.sort_by_key(|(position, weight)| {
+                (u32::MAX - weight, bitboard::to_square(*position))
+            });
+            let moves: Vec<String> = moves
+                .iter()
+                .map(|(position, weight)| format!("{position}:{weight}"))
+                .collect();
+            text += &format!(
+                "{} {} {}\n",
+                board,
+                if *is_black { 'X' } else { 'O' },
+                moves.join(" ")
+            );
+        }
+        text
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.to_text()).map_err(|e| format!("{path}에 쓸 수 없습니다: {e}"))
+    }
+
+    // 기보의 처음부터 max_moves수까지를 정석에 더한다. 수의 무게는 그 수가 두어진 횟수가 된다
+    pub fn add_record(&mut self, record: &Record, max_moves: usize) -> Result<(), String> {
+        let mut board = BitBoard::from_board(&Board::initial());
+        for (color, position) in record
+            .replay()?
+            .into_iter()
+            .zip(&record.moves)
+            .take(max_moves)
+        {
+            self.add(&board, color, *position, 1);
+            board.place(color, bitboard::to_square(*position));
+        }
+        Ok(())
+    }
+
+    // 정석에 있는 국면의 수
+    pub fn get_position_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    // board에서 color가 둘 수를 정석에서 무게에 따라 랜덤으로 고른다. 정석에 없는 국면이면 None을 반환한다
+    pub fn choose(&self, board: &BitBoard, color: TurnEnum, rng: &mut ThreadRng) -> Option<Vec2> {
+        let (key, _) = normalise(board, color);
+        // 국면 자신이 대칭이면 겹치는 수들 중에서도 랜덤으로 고른다
+        let symmetries: Vec<u32> = (0..SYMMETRIES)
+            .filter(|symmetry| get_key(board, color, *symmetry) == key)
+            .collect();
+        let symmetry = *symmetries.choose(rng)?;
+        let moves: Vec<(Vec2, u32)> = self
+            .positions
+            .get(&key)?
+            .iter()
+            .map(|(position, weight)| (inverse_transform(symmetry, *position), *weight))
+            .collect();
+        moves
+            .choose_weighted(rng, |(_, weight)| *weight)
+            .ok()
+            .map(|(position, _)| *position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // bitboard에 symmetry번째의 대칭 변환을 적용한 국면
+    fn transform_board(symmetry: u32, bitboard: &BitBoard) -> BitBoard {
+        BitBoard {
+            black: transform_bits(symmetry, bitboard.black),
+            white: transform_bits(symmetry, bitboard.white),
+        }
+    }
+
+    // 처음의 국면부터 moves대로 둔 국면
+    fn play(moves: &str) -> BitBoard {
+        let record = Record::parse(moves).unwrap();
+        let mut board = BitBoard::from_board(&Board::initial());
+        for (color, position) in record.replay().unwrap().into_iter().zip(record.moves) {
+            board.place(color, bitboard::to_square(position));
+        }
+        board
+    }
+
+    #[test]
+    fn inverse_transform_undoes_transform() {
+        for symmetry in 0..SYMMETRIES {
+            let mut image = 0u64;
+            for square in 0..64 {
+                let position = bitboard::to_position(square);
+                let transformed = transform(symmetry, position);
+                assert!(transformed.is_valid());
+                let back = inverse_transform(symmetry, transformed);
+                assert_eq!((back.x, back.y), (position.x, position.y));
+                image |= 1 << bitboard::to_square(transformed);
+                assert_eq!(
+                    transform_bits(symmetry, 1 << square),
+                    1 << bitboard::to_square(transformed)
+                );
+            }
+            // 64칸이 모두 다른 칸으로 옮겨진다
+            assert_eq!(image, u64::MAX);
+        }
+    }
+
+    #[test]
+    fn symmetric_positions_share_a_key() {
+        let board = play("f5d6c3");
+        let (key, _) = normalise(&board, TurnEnum::White);
+        for symmetry in 0..SYMMETRIES {
+            let transformed = transform_board(symmetry, &board);
+            assert_eq!(normalise(&transformed, TurnEnum::White).0, key);
+            assert_ne!(normalise(&transformed, TurnEnum::Black).0, key);
+        }
+    }
+
+    #[test]
+    fn choose_transforms_move_for_rotated_position() {
+        let mut book = Book::new();
+        book.add_record(&Record::parse("f5d6c3").unwrap(), 3)
+            .unwrap();
+        assert_eq!(book.get_position_count(), 3);
+
+        // f5d6의 국면은 자신과 겹치는 대칭이 없으므로 수는 하나로 정해진다
+        let board = play("f5d6");
+        let c3 = record::parse_position("c3").unwrap();
+        let mut rng = rand::rng();
+        for symmetry in 0..SYMMETRIES {
+            let transformed = transform_board(symmetry, &board);
+            let position = book
+                .choose(&transformed, TurnEnum::Black, &mut rng)
+                .unwrap();
+            let expected = transform(symmetry, c3);
+            assert_eq!((position.x, position.y), (expected.x, expected.y));
+            assert!(
+                book.choose(&transformed, TurnEnum::White, &mut rng)
+                    .is_none()
+            );
+        }
+
+        // 처음의 국면은 대칭이므로 f5와 겹치는 네 수 중 어느 것이든 놓을 수 있는 수가 된다
+        let initial = BitBoard::from_board(&Board::initial());
+        let legal_moves = initial.get_legal_moves(TurnEnum::Black);
+        for _ in 0..20 {
+            let position = book.choose(&initial, TurnEnum::Black, &mut rng).unwrap();
+            assert_ne!(legal_moves >> bitboard::to_square(position) & 1, 0);
+        }
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let mut book = Book::new();
+        book.add_record(&Record::parse("f5d6c3d3c4").unwrap(), 5)
+            .unwrap();
+        book.add_record(&Record::parse("f5f6e6f4").unwrap(), 4)
+            .unwrap();
+        let text = book.to_text();
+        let parsed = Book::parse(&text).unwrap();
+        assert_eq!(parsed.get_position_count(), book.get_position_count());
+        assert_eq!(parsed.to_text(), text);
+    }
+
+    #[test]
+    fn parse_rejects_illegal_moves() {
+        let initial = Board::initial().to_string();
+        assert!(Book::parse(&format!("{initial} X f5:3 d3")).is_ok());
+        for line in [
+            format!("{initial} X a1"),
+            format!("{initial} X f5:x"),
+            format!("{initial} Z f5"),
+            "XO X f5".to_string(),
+        ] {
+            assert!(Book::parse(&line).is_err(), "{line}");
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use crate::board::Board;
+use crate::{TurnEnum, Vec2};
+
+// 한 수의 기록. 되돌릴 때 쓰도록 놓기 전의 모눈판도 가지고 있다
+pub struct Move {
+    pub color: TurnEnum,
+    pub position: Vec2,
+    pub board: Board, // 돌을 놓기 전의 모눈판
+}
+
+// 기보. 되돌린 수도 다시 하기를 위해 다음 수를 둘 때까지 남겨 둔다
+pub struct History {
+    moves: Vec<Move>,
+    current: usize, // 지금의 국면까지 둔 수의 수
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self {
+            moves: Vec::new(),
+            current: 0,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.moves.clear();
+        self.current = 0;
+    }
+
+    // 새로운 수를 기록한다. 되돌려 두었던 수는 버린다
+    pub fn push(&mut self, board: Board, color: TurnEnum, position: Vec2) {
+        self.moves.truncate(self.current);
+        self.moves.push(Move {
+            color,
+            position,
+            board,
+        });
+        self.current += 1;
+    }
+
+    // 마지막으로 둔 수를 되돌린다. 되돌릴 수가 없으면 None을 반환한다
+    pub fn undo(&mut self) -> Option<&Move> {
+        self.current = self.current.checked_sub(1)?;
+        Some(&self.moves[self.current])
+    }
+
+    // 되돌린 수를 하나 다시 둔다. 다시 둘 수가 없으면 None을 반환한다
+    pub fn redo(&mut self) -> Option<&Move> {
+        let next = self.moves.get(self.current)?;
+        self.current += 1;
+        Some(next)
+    }
+
+    // 한 수를 되돌리고, is_human이 참인 색이 둔 수까지 이어서 되돌린다
+    // 되돌린 국면의 모눈판과 턴을 반환하고, 되돌릴 수가 없으면 None을 반환한다
+    pub fn undo_to_turn(
+        &mut self,
+        is_human: impl Fn(TurnEnum) -> bool,
+    ) -> Option<(Board, TurnEnum)> {
+        let mut position = None;
+        while let Some(last) = self.undo() {
+            position = Some((last.board, last.color));
+            if is_human(last.color) {
+                break;
+            }
+        }
+        position
+    }
+
+    // 한 수를 다시 두고, 다음 수가 is_human이 참인 색의 수가 될 때까지 이어서 다시 둔다
+    // 다시 둔 국면의 모눈판과 턴을 반환하고, 다시 둘 수가 없으면 None을 반환한다
+    pub fn redo_to_turn(
+        &mut self,
+        is_human: impl Fn(TurnEnum) -> bool,
+    ) -> Option<(Board, TurnEnum)> {
+        let mut position = None;
+        while let Some(next) = self.redo() {
+            let mut board = next.board;
+            board.place(next.color, next.position);
+            position = Some((board, next.color.get_opponent()));
+            match self.get_next() {
+                Some(next) if !is_human(next.color) => {}
+                _ => break,
+            }
+        }
+        position
+    }
+
+    // 다음에 다시 둘 수
+    pub fn get_next(&self) -> Option<&Move> {
+        self.moves.get(self.current)
+    }
+
+    // 지금의 국면까지 둔 수
+    pub fn get_moves(&self) -> &[Move] {
+        &self.moves[..self.current]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 처음으로 놓을 수 있는 칸에 color의 돌을 놓고 기보에 기록한다
+    fn play(history: &mut History, board: &mut Board, color: TurnEnum) -> Vec2 {
+        let position = board.get_legal_moves(color)[0];
+        history.push(*board, color, position);
+        board.place(color, position);
+        position
+    }
+
+    // 흑이 플레이어이고 백이 컴퓨터인 1P 대국을 4수 둔다
+    fn one_player_game() -> (History, Vec<String>) {
+        let mut history = History::new();
+        let mut board = Board::initial();
+        let mut boards = vec![board.to_string()];
+        for color in [TurnEnum::Black, TurnEnum::White].repeat(2) {
+            play(&mut history, &mut board, color);
+            boards.push(board.to_string());
+        }
+        (history, boards)
+    }
+
+    fn is_black(color: TurnEnum) -> bool {
+        color == TurnEnum::Black
+    }
+
+    #[test]
+    fn undo_and_redo_skip_the_computer() {
+        let (mut history, boards) = one_player_game();
+
+        // 컴퓨터의 응수와 함께 플레이어의 수를 되돌려 플레이어의 이전 턴으로 돌아간다
+        let (board, turn) = history.undo_to_turn(is_black).unwrap();
+        assert_eq!(board.to_string(), boards[2]);
+        assert!(turn == TurnEnum::Black);
+        assert_eq!(history.get_moves().len(), 2);
+
+        let (board, turn) = history.undo_to_turn(is_black).unwrap();
+        assert_eq!(board.to_string(), boards[0]);
+        assert!(turn == TurnEnum::Black);
+        assert!(history.undo_to_turn(is_black).is_none());
+
+        // 다시 둘 때도 컴퓨터의 응수까지 두어 플레이어의 턴으로 나아간다
+        let (board, turn) = history.redo_to_turn(is_black).unwrap();
+        assert_eq!(board.to_string(), boards[2]);
+        assert!(turn == TurnEnum::Black);
+
+        let (board, turn) = history.redo_to_turn(is_black).unwrap();
+        assert_eq!(board.to_string(), boards[4]);
+        assert!(turn == TurnEnum::Black);
+        assert!(history.redo_to_turn(is_black).is_none());
+    }
+
+    #[test]
+    fn two_players_undo_one_move_at_a_time() {
+        let (mut history, boards) = one_player_game();
+        let (board, turn) = history.undo_to_turn(|_| true).unwrap();
+        assert_eq!(board.to_string(), boards[3]);
+        assert!(turn == TurnEnum::White);
+        let (board, turn) = history.redo_to_turn(|_| true).unwrap();
+        assert_eq!(board.to_string(), boards[4]);
+        assert!(turn == TurnEnum::Black);
+    }
+
+    #[test]
+    fn push_after_undo_drops_the_redo_tail() {
+        let (mut history, _) = one_player_game();
+        let (mut board, turn) = history.undo_to_turn(is_black).unwrap();
+        assert!(history.get_next().is_some());
+
+        // 되돌린 국면에서 다른 수를 두면 되돌려 두었던 수는 다시 둘 수 없다
+        let moves = board.get_legal_moves(turn);
+        let position = *moves.last().unwrap();
+        history.push(board, turn, position);
+        board.place(turn, position);
+        assert_eq!(history.get_moves().len(), 3);
+        assert!(history.get_next().is_none());
+        assert!(history.redo().is_none());
+    }
+
+    #[test]
+    fn moves_are_listed_in_order() {
+        let mut history = History::new();
+        let mut board = Board::initial();
+        let mut positions = Vec::new();
+        for color in [TurnEnum::Black, TurnEnum::White].repeat(3) {
+            let position = play(&mut history, &mut board, color);
+            positions.push((position.x, position.y));
+        }
+        let moves: Vec<_> = history
+            .get_moves()
+            .iter()
+            .map(|m| (m.position.x, m.position.y))
+            .collect();
+        assert_eq!(moves, positions);
+        assert!(history.get_moves()[0].color == TurnEnum::Black);
+        assert!(history.get_moves()[5].color == TurnEnum::White);
+
+        // 되돌리면 지금의 국면까지의 수만 남는다
+        history.undo();
+        history.undo();
+        assert_eq!(history.get_moves().len(), 4);
+        history.clear();
+        assert!(history.get_moves().is_empty());
+    }
+}
@@ -1,5 +1,25 @@
+use std::fmt;
+use std::io::{self, IsTerminal};
+
 use getch_rs::{Getch, Key};
-use rand::{rngs::ThreadRng, seq::IndexedRandom};
+use rand::rngs::ThreadRng;
+
+mod ai;
+mod bitboard;
+mod board;
+mod book;
+mod history;
+mod network;
+mod perft;
+mod protocol;
+mod record;
+
+use bitboard::BitBoard;
+use board::Board;
+use book::Book;
+use history::History;
+use network::{Connection, Message};
+use record::Record;
 
 // [2]상수를 정의하는 곳
 
@@ -74,6 +94,72 @@ impl ModeEnum {
     }
 }
 
+// 컴퓨터의 강함의 종류를 정의한다
+#[derive(Clone, Copy, PartialEq)]
+enum LevelEnum {
+    Random = 0, // 놓을 수 있는 곳에서 랜덤으로 고른다
+    Easy = 1,
+    Normal = 2,
+    Hard = 3,
+    Max = 4,
+}
+
+impl TryFrom<usize> for LevelEnum {
+    type Error = ();
+
+    fn try_from(v: usize) -> Result<Self, Self::Error> {
+        match v {
+            x if x == LevelEnum::Random as usize => Ok(LevelEnum::Random),
+            x if x == LevelEnum::Easy as usize => Ok(LevelEnum::Easy),
+            x if x == LevelEnum::Normal as usize => Ok(LevelEnum::Normal),
+            x if x == LevelEnum::Hard as usize => Ok(LevelEnum::Hard),
+            _ => Err(()),
+        }
+    }
+}
+
+impl LevelEnum {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().as_str() {
+            "random" => Ok(LevelEnum::Random),
+            "easy" => Ok(LevelEnum::Easy),
+            "normal" => Ok(LevelEnum::Normal),
+            "hard" => Ok(LevelEnum::Hard),
+            _ => Err(format!(
+                "알 수 없는 강함 \"{name}\": random, easy, normal, hard 중 하나를 지정하세요"
+            )),
+        }
+    }
+    pub fn increase(&mut self) {
+        *self = ((*self as usize + 1) % Self::Max as usize)
+            .try_into()
+            .unwrap();
+    }
+    pub fn decrease(&mut self) {
+        *self = ((*self as usize + Self::Max as usize - 1) % Self::Max as usize)
+            .try_into()
+            .unwrap();
+    }
+}
+
+// 힌트와 마지막 수를 칠하는 256색 팔레트의 번호
+const HINT_COLOUR: u8 = 45;
+const LAST_MOVE_COLOUR: u8 = 196;
+const FLIPPED_COLOUR: u8 = 214;
+
+// 터미널에 색을 쓸지 여부. 출력이 터미널이 아니거나 NO_COLOR가 설정되어 있거나
+// TERM이 dumb이면 쓰지 않는다
+fn supports_colour() -> bool {
+    io::stdout().is_terminal()
+        && std::env::var_os("NO_COLOR").is_none()
+        && std::env::var_os("TERM").is_none_or(|term| term != "dumb")
+}
+
+// 뒤집히는 돌의 수를 나타내는 힌트의 글자. 한 수로 뒤집히는 돌은 많아야 18개이므로 ①~⑳으로 충분하다
+fn get_hint_aa(flips: u32) -> char {
+    char::from_u32(0x2460 + flips.clamp(1, 20) - 1).unwrap()
+}
+
 // [4-1]벡터 구조체를 선언한다
 #[derive(Clone, Copy, Default)]
 struct Vec2 {
@@ -92,11 +178,18 @@ impl Vec2 {
     }
 }
 
+// 「f5」처럼 열을 a~h, 행을 1~8로 나타낸다
+impl fmt::Display for Vec2 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", (b'a' + self.x as u8) as char, self.y + 1)
+    }
+}
+
 struct FixedResources {
     disk_aa: [String; TurnEnum::Max as usize],
     turn_names: [String; TurnEnum::Max as usize],
     mode_names: [String; ModeEnum::Max as usize],
-    directions: [Vec2; DirectionEnum::Max as usize],
+    level_names: [String; LevelEnum::Max as usize],
 }
 
 impl FixedResources {
@@ -112,16 +205,12 @@ impl FixedResources {
                 "2P GAME".to_string(),
                 "WATCH".to_string(),
             ],
-            // [5-4]방향을 선언한다
-            directions: [
-                Vec2 { x: 0, y: -1 },
-                Vec2 { x: -1, y: -1 },
-                Vec2 { x: -1, y: 0 },
-                Vec2 { x: -1, y: 1 },
-                Vec2 { x: 0, y: 1 },
-                Vec2 { x: 1, y: 1 },
-                Vec2 { x: 1, y: 0 },
-                Vec2 { x: 1, y: -1 },
+            // 컴퓨터의 강함의 이름을 선언한다
+            level_names: [
+                "RANDOM".to_string(),
+                "EASY".to_string(),
+                "NORMAL".to_string(),
+                "HARD".to_string(),
             ],
         }
     }
@@ -129,11 +218,20 @@ impl FixedResources {
 
 struct Context {
     resource: FixedResources,
-    board: Vec<TurnEnum>,
+    board: Board,
+    history: History, // 기보
     cursor_position: Vec2,
     turn: TurnEnum,
     mode: ModeEnum,
+    level: LevelEnum, // 컴퓨터의 강함
     is_player: [bool; TurnEnum::Max as usize],
+    message: String,             // 모눈판 아래에 표시하는 메시지
+    player_names: [String; 2],   // 기보에 쓰는 대국자의 이름. 비어 있으면 자동으로 정한다
+    replaying: bool,             // 기보를 재생하는 중인지 여부
+    network: Option<Connection>, // 네트워크 대전의 상대와의 접속
+    show_hints: bool,            // 놓을 수 있는 칸에 뒤집히는 돌의 수를 표시할지 여부
+    use_colour: bool,            // 힌트와 마지막 수를 색으로 칠할지 여부
+    book: Option<Book>,          // 컴퓨터가 쓰는 정석
     g: Getch,
     rng: ThreadRng,
 }
@@ -143,158 +241,76 @@ impl Context {
         Self {
             resource: FixedResources::new(),
             // [5-5]모눈판 각 칸의 상태를 선언한다
-            board: vec![TurnEnum::None; BOARD_HEIGHT * BOARD_WIDTH],
+            board: Board::new(),
+            history: History::new(),
             // [5-6]커서의 좌표를 선언한다
             cursor_position: Vec2::default(),
             // [5-7]현재의 턴을 선언한다
             turn: TurnEnum::Black,
             // [5-8]현재의 게임 모드를 선언한다
             mode: ModeEnum::Max,
+            level: LevelEnum::Normal,
             // [5-9]각 턴이 플레이어인지 여부를 선언한다
             is_player: [false; TurnEnum::Max as usize],
+            message: String::new(),
+            player_names: [String::new(), String::new()],
+            replaying: false,
+            network: None,
+            show_hints: false,
+            use_colour: supports_colour(),
+            book: None,
             g: Getch::new(),
             rng: rand::rng(),
         }
     }
 
-    // [6-2]돌을 놓을 수 있는지 여부의 판정, 또는 돌을 뒤집는 함수를 선언한다
-    pub fn check_can_place(&mut self, color: TurnEnum, position: Vec2, turn_over: bool) -> bool {
-        let mut can_place = false; // [6-2-1]돌을 놓을 수 있는지 여부의 플래그를 선언한다
-
-        // [6-2-2]대상 좌표에 돌이 놓여 있지 않은지 여부를 판정한다
-        if self.get_board(position.x, position.y) != TurnEnum::None {
-            return false; // [6-2-3]돌이 놓여 있으면 놓을 수 없다는 결과를 반환한다
-        }
-
-        // [6-2-4]상대의 돌 색을 선언한다
-        let opponent = color.get_opponent();
-        // if color == TurnEnum::Black {
-        //     TurnEnum::White
-        // } else {
-        //     TurnEnum::Black
-        // };
-
-        // [6-2-5]모든 방향을 반복한다
-        for i in 0..DirectionEnum::Max as usize {
-            // [6-2-6]현재 체크 중인 좌표를 선언한다
-            let mut current_position = position;
-
-            // [6-2-7]옆의 칸으로 이동한다
-            current_position.add(&self.get_direction(i));
-
-            // [6-2-7.1]체크하는 칸이 모눈판의 범위 내인지 판정한다
-            if !current_position.is_valid() {
-                // [6-2-7.2]대상 방향의 체크를 스킵한다
-                continue;
-            }
-
-            // [6-2-8]상대의 돌이 아닌지 판정한다
-            if self.get_board(current_position.x, current_position.y) != opponent {
-                // [6-2-9]상대의 돌이 아니면 그 방향의 체크를 중지한다
-                continue;
-            }
-
-            // [6-2-10]무한 루프한다
-            loop {
-                // [6-2-11]옆 칸으로 이동한다
-                current_position.add(&self.get_direction(i));
-
-                // [6-2-12]체크하는 칸이 모눈판의 범위 내인지 판정한다
-                if !current_position.is_valid() {
-                    // [6-2-13]모눈판 바깥쪽으로 나가면 현재 방향의 체크를 빠져나간다
-                    break;
-                }
-
-                // [6-2-14]체크하는 칸에 돌이 있는지 여부를 판정한다
-                if self.get_board(current_position.x, current_position.y) == TurnEnum::None {
-                    break; // [6-2-15]돌이 없으면 현재 방향의 체크를 빠져나간다
-                }
-
-                // [6-2-16]체크하는 칸에 자신의 돌이 있으면
-                if self.get_board(current_position.x, current_position.y) == color {
-                    // [6-2-17]돌을 놓을 수 있는 것이 확정된다
-                    can_place = true;
-
-                    // [6-2-18]뒤집기 플래그가 설정되어 있는지 여부를 판정한다
-                    if turn_over {
-                        // [6-2-19]뒤집는 좌표를 선언한다
-                        let mut reverse_position = position;
-
-                        // [6-2-20]옆 칸으로 이동한다
-                        reverse_position.add(&self.get_direction(i));
-
-                        // [6-2-21]현재 턴의 돌을 찾을 때까지 반복한다
-                        loop {
-                            // [6-2-22]상대의 돌을 뒤집는다
-                            self.set_board(reverse_position.x, reverse_position.y, color);
+    // [6-5]화면을 그리는 함수를 선언한다
+    pub fn draw_screen(&self) {
+        clearscreen::clear().unwrap();
 
-                            // [6-2-23]옆 칸으로 이동한다
-                            reverse_position.add(&self.get_direction(i));
+        let bitboard = BitBoard::from_board(&self.board);
 
-                            // 크래시 막기
-                            if !reverse_position.is_valid() {
-                                break;
-                            }
+        // 힌트를 표시하는 칸. 플레이어의 턴에만 표시한다
+        let hints = if self.show_hints && self.is_human_player(self.turn) {
+            bitboard.get_legal_moves(self.turn)
+        } else {
+            0
+        };
 
-                            if self.get_board(reverse_position.x, reverse_position.y) == color {
-                                break;
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        can_place // [6-2-24]돌을 놓을 수 있는지 여부를 반환한다
-    }
+        // 마지막 수로 놓인 돌과 뒤집힌 돌
+        let last_move = self.history.get_moves().last();
+        let (placed, flipped) = last_move.map_or((0, 0), |last| {
+            let placed = 1 << bitboard::to_square(last.position);
+            let before = BitBoard::from_board(&last.board).get_own(last.color);
+            (placed, bitboard.get_own(last.color) & !before & !placed)
+        });
 
-    // [6-3]모눈판 위에 돌을 놓을 수 있는 칸이 있는지 여부를 판정하는 함수를 선언한다
-    fn check_can_place_all(&mut self, color: TurnEnum) -> bool {
         for y in 0..BOARD_HEIGHT {
             for x in 0..BOARD_WIDTH {
-                // [6-3-3]판정하는 좌표를 선언한다
-                let position = Vec2 {
-                    x: x as i8,
-                    y: y as i8,
+                let square = (y * BOARD_WIDTH + x) as u32;
+                let bit = 1 << square;
+                let (aa, colour) = if hints & bit != 0 {
+                    let flips = bitboard.get_flips(self.turn, square).count_ones();
+                    (get_hint_aa(flips).to_string(), Some(HINT_COLOUR))
+                } else {
+                    let aa = self.get_disk_aa(self.get_board(x as i8, y as i8) as usize);
+                    let colour = if placed & bit != 0 {
+                        Some(LAST_MOVE_COLOUR)
+                    } else if flipped & bit != 0 {
+                        Some(FLIPPED_COLOUR)
+                    } else {
+                        None
+                    };
+                    (aa.clone(), colour)
                 };
-
-                // [6-3-4]대상 좌표에 돌을 놓을 수 있는지 여부를 판정한다
-                if self.check_can_place(color, position, false) {
-                    return true; // [6-3-5]돌을 놓을 수 있는 칸이 있다는 결과를 반환한다
+                match colour {
+                    Some(colour) if self.use_colour => print!("\x1b[38;5;{colour}m{aa}\x1b[0m"),
+                    _ => print!("{aa}"),
                 }
             }
-        }
-        false // [6-3-6]돌을 놓을 수 있는 칸이 없다는 결과를 반환한다
-    }
-
-    // [6-4]임의의 돌의 개수를 세는 함수를 선언한다
-    fn get_disk_count(&self, color: TurnEnum) -> u32 {
-        let mut count = 0; // [6-4-1]세는 돌의 개수를 보유하는 변수를 선언한다
-
-        for y in 0..BOARD_HEIGHT {
-            for x in 0..BOARD_WIDTH {
-                // [6-4-4]대상 칸에 대상의 돌이 있는지 여부를 판정한다
-                if self.get_board(x as i8, y as i8) == color {
-                    count += 1; // [6-4-5]돌의 개수를 더한다
-                }
-            }
-        }
-        count // [6-4-6]센 돌의 개수를 반환한다
-    }
-
-    // [6-5]화면을 그리는 함수를 선언한다
-    pub fn draw_screen(&self) {
-        clearscreen::clear().unwrap();
-
-        for y in 0..BOARD_HEIGHT {
-            for x in 0..BOARD_WIDTH {
-                print!(
-                    "{}",
-                    self.get_disk_aa(self.get_board(x as i8, y as i8) as usize)
-                );
-            }
 
             // [6-5-5]플레이어의 담당인지 여부를 판정한다
-            if self.is_human_player(self.turn) {
+            if self.is_human_player(self.turn) && !self.replaying {
                 // [6-5-6]대상 행이 커서와 같은 행인지 여부를 판정한다
                 if y == self.cursor_position.y as usize {
                     print!("←");
@@ -305,7 +321,7 @@ impl Context {
         }
 
         // [6-5-9]플레이어의 담당인지 여부를 판정한다
-        if self.is_human_player(self.turn) {
+        if self.is_human_player(self.turn) && !self.replaying {
             for x in 0..BOARD_WIDTH {
                 if x == self.cursor_position.x as usize {
                     print!("↑");
@@ -323,9 +339,9 @@ impl Context {
             println!("{}의 턴입니다.", self.get_turn_names(self.turn));
         } else {
             // [6-5-17]승부가 났다면
-            let black_count = self.get_disk_count(TurnEnum::Black);
+            let black_count = self.board.get_disk_count(TurnEnum::Black);
 
-            let white_count = self.get_disk_count(TurnEnum::White);
+            let white_count = self.board.get_disk_count(TurnEnum::White);
 
             // [6-5-21]승자를 판정한다
             let winner = if black_count > white_count {
@@ -354,6 +370,38 @@ impl Context {
                 println!("{}의 승리", self.get_turn_names(winner));
             }
         }
+
+        // 색을 쓸 수 없는 터미널에서도 알 수 있도록 마지막 수를 글로도 표시한다
+        if let Some(last) = last_move {
+            println!(
+                "마지막 수: {} {} ({}개 뒤집음)",
+                self.get_turn_names(last.color),
+                last.position,
+                flipped.count_ones()
+            );
+        }
+
+        if !self.message.is_empty() {
+            println!("{}", self.message);
+        }
+
+        // 지금까지 둔 수를 10수씩 표시한다
+        println!();
+        for (i, moves) in self.history.get_moves().chunks(10).enumerate() {
+            let positions: Vec<String> = moves.iter().map(|m| m.position.to_string()).collect();
+            println!("{:>2}: {}", i * 10 + 1, positions.join(" "));
+        }
+
+        // 쓸 수 있는 키를 표시한다
+        if self.replaying {
+            println!("\na: 이전 수  d: 다음 수  w: 처음  s: 마지막  h: 힌트  Esc: 끝내기");
+        } else if self.network.is_some() {
+            println!("\nh: 힌트  p: 기보 저장  Esc: 끝내기");
+        } else if self.is_player.contains(&true) {
+            println!("\nh: 힌트  u: 되돌리기  r: 다시 하기  p: 기보 저장");
+        } else if self.turn == TurnEnum::None {
+            println!("\np: 기보 저장");
+        }
     }
 
     // [6-6]모드 선택 화면의 함수를 선언한다
@@ -372,6 +420,12 @@ impl Context {
                 println!("{}\n", self.get_mode_names(i));
             }
 
+            // 컴퓨터의 강함을 표시한다
+            println!(
+                "\n컴퓨터의 강함 (a/d로 변경): < {} >",
+                self.get_level_names(self.level)
+            );
+
             match self.g.getch() {
                 Ok(Key::Char('w')) => {
                     self.mode.decrease(); // [6-6-12]이전 모드로 바꾼다
@@ -379,6 +433,8 @@ impl Context {
                 Ok(Key::Char('s')) => {
                     self.mode.increase(); // [6-6-14]다음 모드로 바꾼다
                 }
+                Ok(Key::Char('a')) => self.level.decrease(),
+                Ok(Key::Char('d')) => self.level.increase(),
                 Ok(Key::Esc) => {
                     std::process::exit(0);
                 }
@@ -410,26 +466,22 @@ impl Context {
 
     // [6-7]게임을 초기화하는 함수를 선언한다
     pub fn init(&mut self) {
-        // [6-7-3]대상 칸을 돌이 놓여 있지 않은 상태로 한다
-        self.board = vec![TurnEnum::None; BOARD_HEIGHT * BOARD_WIDTH];
-
-        // [6-7-4]모눈판 중앙의 오른쪽 위와 왼쪽 아래에 검은 돌을 놓는다
-        self.set_board(3, 4, TurnEnum::Black);
-        self.set_board(4, 3, TurnEnum::Black);
-
-        // [6-7-5]모눈판 중앙의 왼쪽 위와 오른쪽 아래에 흰 돌을 놓는다
-        self.set_board(3, 3, TurnEnum::White);
-        self.set_board(4, 4, TurnEnum::White);
+        // [6-7-3]대상 칸을 돌이 놓여 있지 않은 상태로 하고 중앙에 네 개의 돌을 놓는다
+        self.board = Board::initial();
+        self.history.clear();
 
         self.turn = TurnEnum::Black; // [6-7-6]검은 돌의 턴으로 초기화한다
 
         self.cursor_position = Vec2 { x: 3, y: 3 }; // [6-7-7]커서의 좌표를 초기화한다
 
+        self.message.clear();
+
         self.draw_screen();
     }
 
     // [6-8]돌을 놓는 칸을 선택하는 함수를 선언한다
-    pub fn input_position(&mut self) -> Vec2 {
+    // 수를 되돌리거나 다시 두어 국면이 바뀌었으면 None을 반환한다
+    pub fn input_position(&mut self) -> Option<Vec2> {
         loop {
             self.draw_screen();
 
@@ -450,13 +502,35 @@ impl Context {
                     // [6-8-11]커서를 오른쪽으로 이동한다
                     self.cursor_position.x += 1;
                 }
+                Ok(Key::Char('u' | 'r')) if self.network.is_some() => {
+                    self.message =
+                        "네트워크 대전에서는 되돌리거나 다시 둘 수 없습니다.".to_string();
+                }
+                Ok(Key::Char('u')) => {
+                    if self.undo() {
+                        return None;
+                    }
+                    self.message = "되돌릴 수가 없습니다.".to_string();
+                }
+                Ok(Key::Char('r')) => {
+                    if self.redo() {
+                        return None;
+                    }
+                    self.message = "다시 둘 수가 없습니다.".to_string();
+                }
+                Ok(Key::Char('p')) => self.save_record(),
+                Ok(Key::Char('h')) => self.show_hints = !self.show_hints,
                 Ok(Key::Esc) => {
+                    self.disconnect();
                     std::process::exit(0);
                 }
                 _ => {
                     // [6-8-13]커서의 좌표에 돌을 놓을 수 있는지 여부를 판정한다
-                    if self.check_can_place(self.turn, self.cursor_position, false) {
-                        return self.cursor_position;
+                    if self
+                        .board
+                        .check_can_place(self.turn, self.cursor_position, false)
+                    {
+                        return Some(self.cursor_position);
                     } else {
                         // [6-8-15]놓을 수 없다면
                         println!("놓을 수 없는 곳입니다.");
@@ -476,12 +550,194 @@ impl Context {
         }
     }
 
-    fn get_board(&self, x: i8, y: i8) -> TurnEnum {
-        self.board[y as usize * BOARD_WIDTH + x as usize]
+    // position에 돌을 놓고 기보에 기록한다
+    fn play(&mut self, position: Vec2) {
+        self.history.push(self.board, self.turn, position);
+
+        // [6-9-29]돌을 뒤집고 [6-9-30]현재 턴의 돌을 놓는다
+        self.board.place(self.turn, position);
+    }
+
+    // 한 수를 되돌린다. 1P에서는 컴퓨터의 수도 함께 되돌려 플레이어의 이전 턴까지 돌아간다
+    // 되돌렸으면 true를 반환한다
+    fn undo(&mut self) -> bool {
+        let is_player = self.is_player;
+        let Some((board, turn)) = self.history.undo_to_turn(|color| is_player[color as usize])
+        else {
+            return false;
+        };
+        self.board = board;
+        self.turn = turn;
+        self.message.clear();
+        true
+    }
+
+    // 되돌린 수를 다시 둔다. 1P에서는 이어지는 컴퓨터의 수도 다시 두어 플레이어의 턴까지 나아간다
+    // 다시 두었으면 true를 반환한다
+    fn redo(&mut self) -> bool {
+        let is_player = self.is_player;
+        let Some((board, turn)) = self.history.redo_to_turn(|color| is_player[color as usize])
+        else {
+            return false;
+        };
+        self.board = board;
+        self.turn = turn;
+        self.message.clear();
+        true
+    }
+
+    // 놓을 수 있는 칸이 없으면 상대의 턴으로 하고, 양쪽 모두 없으면 승부가 난 것으로 한다
+    fn settle_turn(&mut self) {
+        if self.turn != TurnEnum::None && !self.board.check_can_place_all(self.turn) {
+            self.flip_turn();
+            if !self.board.check_can_place_all(self.turn) {
+                self.turn = TurnEnum::None;
+            }
+        }
+    }
+
+    // 기보의 수를 처음의 국면부터 차례로 두어 마지막 국면으로 한다
+    fn load_record(&mut self, record: &Record) {
+        // 기보는 읽어 들일 때 확인했으므로 실패하지 않는다
+        let colors = record.replay().unwrap();
+        for (color, position) in colors.into_iter().zip(&record.moves) {
+            self.turn = color;
+            self.play(*position);
+            self.flip_turn();
+        }
+    }
+
+    // 기보를 한 수씩 재생한다
+    fn view_record(&mut self, record: &Record) {
+        // 양쪽을 플레이어로 해 두면 되돌리기와 다시 하기가 한 수씩 진행한다
+        self.set_human_player(TurnEnum::Black, true);
+        self.set_human_player(TurnEnum::White, true);
+        self.replaying = true;
+        self.init();
+        self.load_record(record);
+        while self.undo() {}
+
+        loop {
+            self.settle_turn();
+            self.message = format!(
+                "{} - {}  {}  결과 {}  ({}/{}수)",
+                record.black,
+                record.white,
+                record.date,
+                record.result,
+                self.history.get_moves().len(),
+                record.moves.len()
+            );
+            self.draw_screen();
+
+            match self.g.getch() {
+                Ok(Key::Char('a')) => {
+                    self.undo();
+                }
+                Ok(Key::Char('d')) => {
+                    self.redo();
+                }
+                Ok(Key::Char('w')) => while self.undo() {},
+                Ok(Key::Char('s')) => while self.redo() {},
+                Ok(Key::Char('h')) => self.show_hints = !self.show_hints,
+                Ok(Key::Esc) => return,
+                _ => {}
+            }
+        }
+    }
+
+    // 기보에 쓰는 대국자의 이름. 명령줄에서 지정하지 않았으면 플레이어나 컴퓨터의 강함으로 한다
+    fn get_player_name(&self, turn: TurnEnum) -> String {
+        let name = &self.player_names[turn as usize];
+        if !name.is_empty() {
+            name.clone()
+        } else if self.is_human_player(turn) {
+            "플레이어".to_string()
+        } else {
+            format!("컴퓨터 ({})", self.get_level_names(self.level))
+        }
+    }
+
+    // 지금의 국면까지의 기보를 「reversi-날짜-시각.txt」에 저장한다
+    fn save_record(&mut self) {
+        let (date, time) = record::get_date_time();
+        let result = if self.turn == TurnEnum::None {
+            format!(
+                "{}-{}",
+                self.board.get_disk_count(TurnEnum::Black),
+                self.board.get_disk_count(TurnEnum::White)
+            )
+        } else {
+            "*".to_string()
+        };
+        let path = format!(
+            "reversi-{}-{}.txt",
+            date.replace('-', ""),
+            time.replace(':', "")
+        );
+        let record = Record {
+            black: self.get_player_name(TurnEnum::Black),
+            white: self.get_player_name(TurnEnum::White),
+            date,
+            result,
+            moves: self
+                .history
+                .get_moves()
+                .iter()
+                .map(|m| m.position)
+                .collect(),
+        };
+        self.message = match record.save(&path) {
+            Ok(()) => format!("기보를 {path}에 저장했습니다."),
+            Err(e) => e,
+        };
+    }
+
+    // 네트워크 대전을 준비한다. 자신은 local의 돌을, 상대는 반대의 돌을 담당한다
+    fn start_network(&mut self, connection: Connection, local: TurnEnum, remote_name: String) {
+        self.set_human_player(local, true);
+        self.set_human_player(local.get_opponent(), false);
+        self.player_names[local.get_opponent() as usize] = remote_name;
+        self.network = Some(connection);
     }
 
-    fn set_board(&mut self, x: i8, y: i8, color: TurnEnum) {
-        self.board[y as usize * BOARD_WIDTH + x as usize] = color;
+    // 자신이 둔 수를 둔 뒤의 모눈판과 함께 상대에게 보낸다
+    fn send_move(&mut self, position: Vec2) -> Result<(), String> {
+        let board = BitBoard::from_board(&self.board);
+        match &mut self.network {
+            Some(connection) => connection.send(&Message::Move { position, board }),
+            None => Ok(()),
+        }
+    }
+
+    // 상대가 둔 수를 받는다. 받아들일 수 없는 수이면 상대에게도 이유를 알리고 오류를 반환한다
+    fn receive_move(&mut self) -> Result<Vec2, String> {
+        match &mut self.network {
+            Some(connection) => connection.receive_move(&self.board, self.turn),
+            None => Err("상대와 접속되어 있지 않습니다".to_string()),
+        }
+    }
+
+    // 네트워크 대전을 그만둔다는 것을 상대에게 알리고 접속을 닫는다
+    fn disconnect(&mut self) {
+        if let Some(mut connection) = self.network.take() {
+            let _ = connection.send(&Message::Bye);
+        }
+    }
+
+    // 네트워크 대전을 계속할 수 없을 때 이유를 표시하고 끝낸다. 끝내기 전에 p로 기보를 저장할 수 있다
+    fn abort_network(&mut self, reason: &str) -> ! {
+        self.message = format!("{reason}. 아무 키나 누르면 끝납니다.");
+        self.draw_screen();
+        while let Ok(Key::Char('p')) = self.g.getch() {
+            self.save_record();
+            self.draw_screen();
+        }
+        std::process::exit(1);
+    }
+
+    fn get_board(&self, x: i8, y: i8) -> TurnEnum {
+        self.board.get(x, y)
     }
 
     fn get_disk_aa(&self, turn: usize) -> &String {
@@ -496,8 +752,8 @@ impl Context {
         &self.resource.mode_names[mode]
     }
 
-    fn get_direction(&self, dir: usize) -> Vec2 {
-        self.resource.directions[dir]
+    fn get_level_names(&self, level: LevelEnum) -> &String {
+        &self.resource.level_names[level as usize]
     }
 
     fn is_human_player(&self, turn: TurnEnum) -> bool {
@@ -513,25 +769,282 @@ impl Context {
     }
 }
 
+const USAGE: &str = "사용법: ch3_reversi [옵션]
+  --load 파일      저장한 기보를 읽어 들여 그 국면부터 이어서 둔다
+  --replay 파일    저장한 기보를 한 수씩 재생한다
+  --level 강함     컴퓨터의 강함 (random, easy, normal, hard)
+  --engine         화면 대신 표준 입출력으로 GTP를 본뜬 명령을 주고받는 엔진 모드로 실행한다
+                   list_commands로 명령의 목록을 얻을 수 있다
+  --host 포트      [주소:]포트에서 상대의 접속을 기다려 네트워크 대전을 한다. 호스트는 검은 돌을 담당한다
+                   포트만 쓰면 이 컴퓨터 안(127.0.0.1)에서만 기다린다. 다른 컴퓨터와 대전하려면 0.0.0.0:포트처럼 주소를 쓴다
+  --connect 주소:포트  호스트에 접속해 네트워크 대전을 한다. 접속한 쪽은 흰 돌을 담당한다
+                   대전 중에 상대로부터 5분 동안 아무것도 오지 않으면 연결이 끊어진 것으로 보고 그만둔다
+  --hints          놓을 수 있는 칸에 뒤집히는 돌의 수를 표시한 채로 시작한다 (게임 중에는 h로 바꾼다)
+  --black 이름     기보에 쓰는 검은 돌의 대국자 이름
+  --white 이름     기보에 쓰는 흰 돌의 대국자 이름
+  --book 파일      컴퓨터가 이 정석 파일에 있는 국면에서는 읽지 않고 정석의 수를 둔다 (랜덤 이외)
+  --build-book 파일 기보...
+                   저장한 기보들에서 정석 파일을 만든다. 수의 무게는 그 수가 두어진 횟수가 된다
+  --book-moves 수  --build-book이 기보의 처음부터 정석에 넣는 수 (기본값 20)
+  --perft 깊이     처음의 국면부터 지정한 깊이까지 Board와 BitBoard의 착수 생성을 비교하고 속도를 표시한다
+
+게임 중에 p를 누르면 기보를 「reversi-날짜-시각.txt」에 저장한다
+
+정석 파일은 한 행에 한 국면을 「모눈판 차례 수:무게 수:무게 ...」로 쓴다
+  모눈판은 a1, b1, ..., h8의 순으로 X(검은 돌) O(흰 돌) -(빈칸)을 늘어놓은 64문자, 차례는 X 또는 O
+  「f5:10」은 f5를 무게 10으로 고른다는 뜻이다. '#'으로 시작하는 행은 코멘트
+  회전하거나 뒤집어서 겹치는 국면은 같은 국면으로 본다";
+
+// 정석에 넣는 기보의 처음부터의 수
+const DEFAULT_BOOK_MOVES: usize = 20;
+
+struct Options {
+    load_path: Option<String>,   // 지정하면 이 기보의 국면부터 시작한다
+    replay_path: Option<String>, // 지정하면 이 기보를 재생한다
+    player_names: [String; 2],
+    host_address: Option<String>, // 지정하면 이 주소에서 네트워크 대전의 상대를 기다린다
+    connect_address: Option<String>, // 지정하면 이 주소의 호스트에 접속한다
+    level: LevelEnum,
+    hints: bool,
+    engine: bool, // 엔진 모드로 실행할지 여부
+    book_path: Option<String>,
+    build_book_path: Option<String>, // 지정하면 게임 대신 record_paths의 기보에서 정석을 만든다
+    book_moves: usize,
+    record_paths: Vec<String>,
+    perft_depth: Option<u32>, // 지정하면 게임 대신 착수 생성의 확인을 한다
+}
+
+impl Options {
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Self {
+            load_path: None,
+            replay_path: None,
+            player_names: [String::new(), String::new()],
+            host_address: None,
+            connect_address: None,
+            level: LevelEnum::Normal,
+            hints: false,
+            engine: false,
+            book_path: None,
+            build_book_path: None,
+            book_moves: DEFAULT_BOOK_MOVES,
+            record_paths: Vec::new(),
+            perft_depth: None,
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--load" => {
+                    let value = args.next().ok_or("--load에 파일 이름이 필요합니다")?;
+                    options.load_path = Some(value.clone());
+                }
+                "--replay" => {
+                    let value = args.next().ok_or("--replay에 파일 이름이 필요합니다")?;
+                    options.replay_path = Some(value.clone());
+                }
+                "--black" => {
+                    let value = args.next().ok_or("--black에 이름이 필요합니다")?;
+                    options.player_names[TurnEnum::Black as usize] = value.clone();
+                }
+                "--white" => {
+                    let value = args.next().ok_or("--white에 이름이 필요합니다")?;
+                    options.player_names[TurnEnum::White as usize] = value.clone();
+                }
+                "--host" => {
+                    let value = args.next().ok_or("--host에 포트 번호가 필요합니다")?;
+                    options.host_address = Some(value.clone());
+                }
+                "--connect" => {
+                    let value = args.next().ok_or("--connect에 주소:포트가 필요합니다")?;
+                    options.connect_address = Some(value.clone());
+                }
+                "--level" => {
+                    let value = args.next().ok_or("--level에 강함이 필요합니다")?;
+                    options.level = LevelEnum::parse(value)?;
+                }
+                "--engine" => options.engine = true,
+                "--hints" => options.hints = true,
+                "--book" => {
+                    let value = args.next().ok_or("--book에 파일 이름이 필요합니다")?;
+                    options.book_path = Some(value.clone());
+                }
+                "--build-book" => {
+                    let value = args.next().ok_or("--build-book에 파일 이름이 필요합니다")?;
+                    options.build_book_path = Some(value.clone());
+                }
+                "--book-moves" => {
+                    let value = args.next().ok_or("--book-moves에 수가 필요합니다")?;
+                    options.book_moves =
+                        value.parse().ok().filter(|n| *n > 0).ok_or_else(|| {
+                            format!("--book-moves의 값이 잘못되었습니다: {value}")
+                        })?;
+                }
+                "--perft" => {
+                    let value = args.next().ok_or("--perft에 깊이가 필요합니다")?;
+                    options.perft_depth = Some(
+                        value
+                            .parse()
+                            .map_err(|_| format!("--perft의 깊이가 잘못되었습니다: {value}"))?,
+                    );
+                }
+                _ if !arg.starts_with("--") => options.record_paths.push(arg.clone()),
+                _ => return Err(format!("알 수 없는 옵션입니다: {arg}")),
+            }
+        }
+        if options.build_book_path.is_some() == options.record_paths.is_empty() {
+            return Err(
+                "--build-book에는 정석 파일 하나와 기보 파일이 하나 이상 필요합니다".to_string(),
+            );
+        }
+        if options.host_address.is_some() && options.connect_address.is_some() {
+            return Err("--host와 --connect는 함께 쓸 수 없습니다".to_string());
+        }
+        if (options.host_address.is_some() || options.connect_address.is_some())
+            && (options.load_path.is_some() || options.replay_path.is_some())
+        {
+            return Err("네트워크 대전에서는 --load와 --replay를 쓸 수 없습니다".to_string());
+        }
+        Ok(options)
+    }
+}
+
+// 기보 파일들의 처음부터 max_moves수까지를 모아 정석 파일을 만든다
+fn build_book(path: &str, record_paths: &[String], max_moves: usize) -> Result<(), String> {
+    let mut book = Book::new();
+    for record_path in record_paths {
+        let record = Record::load(record_path)?;
+        book.add_record(&record, max_moves)?;
+    }
+    book.save(path)?;
+    println!(
+        "{}개의 기보에서 {}개의 국면을 {path}에 썼습니다.",
+        record_paths.len(),
+        book.get_position_count()
+    );
+    Ok(())
+}
+
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let options = Options::parse(&args).unwrap_or_else(|e| {
+        eprintln!("{e}");
+        eprintln!("{USAGE}");
+        std::process::exit(1);
+    });
+
+    if let Some(depth) = options.perft_depth {
+        std::process::exit(if perft::run(depth) { 0 } else { 1 });
+    }
+    if let Some(path) = &options.build_book_path {
+        if let Err(e) = build_book(path, &options.record_paths, options.book_moves) {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let book = options.book_path.as_deref().map(|path| {
+        Book::load(path).unwrap_or_else(|e| {
+            eprintln!("{e}");
+            std::process::exit(1);
+        })
+    });
+    if options.engine {
+        protocol::run(options.level, book);
+        return;
+    }
+
+    // 기보는 게임을 시작하기 전에 읽어 들여 잘못된 파일이면 바로 알린다
+    let load_record = |path: &Option<String>| {
+        path.as_deref().map(|path| {
+            Record::load(path).unwrap_or_else(|e| {
+                eprintln!("{e}");
+                std::process::exit(1);
+            })
+        })
+    };
+    let replay_record = load_record(&options.replay_path);
+    let mut continued_record = load_record(&options.load_path);
+
     let mut ctx = Context::new();
+    ctx.player_names = options.player_names;
+    ctx.level = options.level;
+    ctx.book = book;
+    ctx.show_hints = options.hints;
+
+    if let Some(record) = replay_record {
+        ctx.view_record(&record);
+        return;
+    }
+
+    // 네트워크 대전에서는 호스트가 검은 돌, 접속한 쪽이 흰 돌을 담당한다
+    let network = match (options.host_address, options.connect_address) {
+        (Some(address), _) => Some((address, TurnEnum::Black)),
+        (None, Some(address)) => Some((address, TurnEnum::White)),
+        (None, None) => None,
+    };
+    if let Some((address, local)) = network {
+        ctx.set_human_player(local, true);
+        let name = ctx.get_player_name(local);
+        let connected = if local == TurnEnum::Black {
+            Connection::host(&address, &name)
+        } else {
+            Connection::connect(&address, &name)
+        };
+        match connected {
+            Ok((connection, remote_name)) => ctx.start_network(connection, local, remote_name),
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        }
+    }
+
     'start: loop {
-        ctx.select_mode();
+        // 네트워크 대전은 정해진 모드로 한 번만 한다
+        if ctx.network.is_none() {
+            ctx.select_mode();
+        }
         ctx.init();
+
+        // 처음의 게임만 읽어 들인 기보의 국면부터 시작한다
+        if let Some(record) = continued_record.take() {
+            ctx.load_record(&record);
+        }
         // [6-9-6]메인루프
         loop {
             // [6-9-7]놓을 수 있는 칸이 없는지 여부를 판정한다
-            if !ctx.check_can_place_all(ctx.turn) {
+            if !ctx.board.check_can_place_all(ctx.turn) {
                 // [6-9-8]턴을 바꾼다
                 ctx.flip_turn();
 
                 // [6-9-9]놓을 수 있는 칸이 없는지 여부를 판정한다
-                if !ctx.check_can_place_all(ctx.turn) {
+                if !ctx.board.check_can_place_all(ctx.turn) {
                     ctx.turn = TurnEnum::None; // [6-9-10]승부가 난 것으로 한다
 
                     ctx.draw_screen();
 
-                    let _ = ctx.g.getch();
+                    // p로 기보를 저장할 수 있고, 플레이어가 있으면 u로 마지막 수를 되돌려 계속할 수 있다
+                    let key = loop {
+                        match ctx.g.getch() {
+                            Ok(Key::Char('p')) => {
+                                ctx.save_record();
+                                ctx.draw_screen();
+                            }
+                            key => break key,
+                        }
+                    };
+                    if let Ok(Key::Char('u')) = key
+                        && ctx.is_player.contains(&true)
+                        && ctx.network.is_none()
+                        && ctx.undo()
+                    {
+                        continue;
+                    }
+                    if ctx.network.is_some() {
+                        ctx.disconnect();
+                        return;
+                    }
 
                     ctx.select_mode();
                     ctx.init();
@@ -543,46 +1056,73 @@ fn main() {
             }
 
             // [6-9-16]돌을 놓는 칸을 선언한다
-            let place_position = 
+            let place_position =
 
             // [6-9-17]현재 턴의 담당이 플레이어인지 여부를 판정한다
             if ctx.is_human_player(ctx.turn) {
                 // [6-9-18]돌을 놓는 칸을 선택하는 함수를 호출한다
-                ctx.input_position()
+                match ctx.input_position() {
+                    Some(position) => position,
+                    // 수를 되돌리거나 다시 두었으면 바뀐 국면부터 다시 판정한다
+                    None => continue,
+                }
+            } else if ctx.network.is_some() {
+                // 네트워크 대전의 상대의 턴이라면 상대가 둔 수를 기다린다
+                ctx.message = format!(
+                    "{}의 수를 기다리는 중입니다...",
+                    ctx.get_player_name(ctx.turn)
+                );
+                ctx.draw_screen();
+                match ctx.receive_move() {
+                    Ok(position) => {
+                        ctx.message = format!("{}: {position}", ctx.get_player_name(ctx.turn));
+                        position
+                    }
+                    Err(e) => ctx.abort_network(&e),
+                }
             } else {
                 // [6-9-19]현재 턴의 담당이 플레이어가 아니라면
                 ctx.draw_screen();
 
                 let _ = ctx.g.getch();
 
-                // [6-9-22]놓을 수 있는 좌표를 보유하는 벡터를 선언한다
-                let mut positions = Vec::new();
-
-                for y in 0..BOARD_HEIGHT {
-                    for x in 0..BOARD_WIDTH {
-                        // [6-9-25]대상 칸의 좌표를 선언한다
-                        let position = Vec2 {
-                            x: x as i8,
-                            y: y as i8,
-                        };
-
-                        // [6-9-26]대상 좌표에 돌을 놓을 수 있는지 여부를 판정한다
-                        if ctx.check_can_place(ctx.turn, position, false) {
-                            // [6-9-27]벡터에 대상 좌표를 추가한다
-                            positions.push(position);
-                        }
-                    }
-                }
-
-                // [6-9-28]놓을 수 있는 곳을 랜덤으로 얻는다
-                *positions.choose(&mut ctx.rng).unwrap()
+                println!("생각하는 중입니다...");
+
+                // 컴퓨터의 강함에 따라 수를 읽어 놓을 곳을 고른다
+                let result = ai::choose_move(
+                    &ctx.board,
+                    ctx.turn,
+                    ctx.level,
+                    None,
+                    ctx.book.as_ref(),
+                    &mut ctx.rng,
+                )
+                .unwrap();
+                ctx.message = if result.from_book {
+                    format!("{}: {}  정석", ctx.get_turn_names(ctx.turn), result.position)
+                } else if result.depth == 0 {
+                    format!("{}: {}", ctx.get_turn_names(ctx.turn), result.position)
+                } else {
+                    format!(
+                        "{}: {}  깊이 {}  평가 {:+}  {}국면",
+                        ctx.get_turn_names(ctx.turn),
+                        result.position,
+                        result.depth,
+                        result.score,
+                        result.nodes
+                    )
+                };
+                result.position
             };
 
-            // [6-9-29]돌을 뒤집는다
-            ctx.check_can_place(ctx.turn, place_position, true);
+            ctx.play(place_position);
 
-            // [6-9-30]현재 턴의 돌을 놓는다
-            ctx.set_board(place_position.x, place_position.y, ctx.turn);
+            // 네트워크 대전에서는 자신이 둔 수를 상대에게 보낸다
+            if ctx.is_human_player(ctx.turn)
+                && let Err(e) = ctx.send_move(place_position)
+            {
+                ctx.abort_network(&e);
+            }
 
             // [6-9-31]턴을 바꾼다
             ctx.flip_turn();
@@ -0,0 +1,375 @@
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+use crate::bitboard::BitBoard;
+use crate::board::Board;
+use crate::record;
+use crate::{TurnEnum, Vec2};
+
+// 통신 규약의 판. 서로 다르면 대전하지 않는다
+const PROTOCOL_VERSION: u32 = 1;
+
+// 상대의 메시지를 기다리는 최대 시간. 이 시간 안에 수가 오지 않으면 연결이 끊어진 것으로 본다
+// 상대가 응답하지 않게 되어도 게임이 멈춘 채로 있지 않도록 한다
+const RECEIVE_TIMEOUT: Duration = Duration::from_secs(300);
+
+// 주고받는 메시지. 한 행에 하나씩 텍스트로 보낸다
+//   HELLO reversi 1 이름              처음에 서로 보내는 인사. 판과 대국자의 이름
+//   MOVE f5 0000000810000000 ...      둔 수와 둔 뒤의 검은 돌, 흰 돌의 비트보드(16진수)
+//   BYE                               게임을 그만둔다
+//   ERROR 이유                        상대의 메시지를 받아들일 수 없어 게임을 그만둔다
+// 패스는 양쪽에서 같이 판정할 수 있으므로 보내지 않는다
+pub enum Message {
+    Hello { version: u32, name: String },
+    Move { position: Vec2, board: BitBoard },
+    Bye,
+    Error(String),
+}
+
+impl Message {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let message = match words[..] {
+            ["HELLO", "reversi", version, ..] => {
+                version.parse().ok().map(|version| Message::Hello {
+                    version,
+                    name: words[3..].join(" "),
+                })
+            }
+            ["MOVE", position, black, white] => {
+                record::parse_position(position).and_then(|position| {
+                    Some(Message::Move {
+                        position,
+                        board: BitBoard {
+                            black: u64::from_str_radix(black, 16).ok()?,
+                            white: u64::from_str_radix(white, 16).ok()?,
+                        },
+                    })
+                })
+            }
+            ["BYE"] => Some(Message::Bye),
+            ["ERROR", ..] => Some(Message::Error(words[1..].join(" "))),
+            _ => None,
+        };
+        message.ok_or_else(|| format!("알 수 없는 메시지입니다: {line}"))
+    }
+
+    pub fn to_line(&self) -> String {
+        match self {
+            Message::Hello { version, name } => format!("HELLO reversi {version} {name}"),
+            Message::Move { position, board } => {
+                format!("MOVE {position} {:016x} {:016x}", board.black, board.white)
+            }
+            Message::Bye => "BYE".to_string(),
+            Message::Error(reason) => format!("ERROR {reason}"),
+        }
+    }
+}
+
+// 상대와의 접속
+pub struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    fn new(stream: TcpStream) -> Result<Self, String> {
+        let writer = stream
+            .try_clone()
+            .map_err(|e| format!("접속을 준비할 수 없습니다: {e}"))?;
+        // 수를 한 번에 보내도록 지연 없이 보낸다
+        let _ = writer.set_nodelay(true);
+        stream
+            .set_read_timeout(Some(RECEIVE_TIMEOUT))
+            .map_err(|e| format!("접속을 준비할 수 없습니다: {e}"))?;
+        Ok(Self {
+            reader: BufReader::new(stream),
+            writer,
+        })
+    }
+
+    // address에서 상대의 접속을 하나 기다리고 인사를 주고받는다. 상대의 이름을 함께 반환한다
+    // address가 포트 번호뿐이면 이 컴퓨터 안(127.0.0.1)에서만 기다린다
+    // 다른 컴퓨터에서 접속받으려면 「0.0.0.0:포트」처럼 주소를 명시해야 한다
+    pub fn host(address: &str, name: &str) -> Result<(Self, String), String> {
+        let address = if address.contains(':') {
+            address.to_string()
+        } else {
+            format!("127.0.0.1:{address}")
+        };
+        let listener = TcpListener::bind(&address)
+            .map_err(|e| format!("{address}에서 기다릴 수 없습니다: {e}"))?;
+        println!("{address}에서 상대의 접속을 기다리는 중입니다...");
+        Self::accept(&listener, name)
+    }
+
+    // listener에 오는 접속을 하나 받아 인사를 주고받는다. 상대의 이름을 함께 반환한다
+    fn accept(listener: &TcpListener, name: &str) -> Result<(Self, String), String> {
+        let (stream, peer) = listener
+            .accept()
+            .map_err(|e| format!("접속을 받을 수 없습니다: {e}"))?;
+        println!("{peer}에서 접속했습니다.");
+
+        let mut connection = Self::new(stream)?;
+        let remote_name = connection.receive_hello()?;
+        connection.send_hello(name)?;
+        Ok((connection, remote_name))
+    }
+
+    // address의 상대에게 접속하고 인사를 주고받는다. 상대의 이름을 함께 반환한다
+    pub fn connect(address: &str, name: &str) -> Result<(Self, String), String> {
+        let stream = TcpStream::connect(address)
+            .map_err(|e| format!("{address}에 접속할 수 없습니다: {e}"))?;
+        let mut connection = Self::new(stream)?;
+        connection.send_hello(name)?;
+        let remote_name = connection.receive_hello()?;
+        Ok((connection, remote_name))
+    }
+
+    fn send_hello(&mut self, name: &str) -> Result<(), String> {
+        self.send(&Message::Hello {
+            version: PROTOCOL_VERSION,
+            name: name.to_string(),
+        })
+    }
+
+    fn receive_hello(&mut self) -> Result<String, String> {
+        match self.receive()? {
+            Message::Hello { version, name } if version == PROTOCOL_VERSION => Ok(name),
+            Message::Hello { version, .. } => {
+                let reason =
+                    format!("통신 규약의 판이 다릅니다 (자신 {PROTOCOL_VERSION}, 상대 {version})");
+                let _ = self.send(&Message::Error(reason.clone()));
+                Err(reason)
+            }
+            _ => Err("상대로부터 인사가 오지 않았습니다".to_string()),
+        }
+    }
+
+    pub fn send(&mut self, message: &Message) -> Result<(), String> {
+        writeln!(self.writer, "{}", message.to_line())
+            .and_then(|_| self.writer.flush())
+            .map_err(|e| format!("상대와의 연결이 끊어졌습니다: {e}"))
+    }
+
+    // 다음 메시지가 올 때까지 기다린다. 연결이 끊어지거나 RECEIVE_TIMEOUT이 지나면 오류를 반환한다
+    pub fn receive(&mut self) -> Result<Message, String> {
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) => Err("상대와의 연결이 끊어졌습니다".to_string()),
+            Ok(_) => Message::parse(line.trim()),
+            // 시간이 다 되었을 때 플랫폼에 따라 WouldBlock 또는 TimedOut이 된다
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                Err("상대로부터 응답이 없어 연결이 끊어진 것으로 봅니다".to_string())
+            }
+            Err(e) => Err(format!("상대와의 연결이 끊어졌습니다: {e}")),
+        }
+    }
+
+    // board에서 color를 담당하는 상대가 둔 수를 받는다. 놓을 수 없는 곳이거나 둔 뒤의 모눈판이 어긋나면
+    // 상대에게도 ERROR로 이유를 알리고 오류를 반환한다
+    pub fn receive_move(&mut self, board: &Board, color: TurnEnum) -> Result<Vec2, String> {
+        let error = match self.receive() {
+            Ok(Message::Move {
+                position,
+                board: remote,
+            }) => {
+                let mut next = *board;
+                if !next.check_can_place(color, position, false) {
+                    format!("상대가 놓을 수 없는 곳 {position}에 두었습니다")
+                } else {
+                    next.place(color, position);
+                    if BitBoard::from_board(&next) == remote {
+                        return Ok(position);
+                    }
+                    format!("{position}을(를) 둔 뒤의 모눈판이 상대와 어긋났습니다")
+                }
+            }
+            Ok(Message::Bye) => return Err("상대가 게임을 그만두었습니다".to_string()),
+            Ok(Message::Error(reason)) => {
+                return Err(format!("상대가 오류를 알렸습니다: {reason}"));
+            }
+            Ok(Message::Hello { .. }) => "게임 중에 인사를 받았습니다".to_string(),
+            Err(e) => e,
+        };
+        // 이미 끊어졌으면 보내지 못해도 상관없다
+        let _ = self.send(&Message::Error(error.clone()));
+        Err(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Instant;
+
+    use super::*;
+    use crate::bitboard;
+
+    // 127.0.0.1의 빈 포트에서 기다리고, 다른 스레드에서 접속한 상대에게 client를 실행시킨다
+    // 호스트 쪽의 접속과 상대의 이름, client의 결과를 받는 핸들을 반환한다
+    fn connect_pair<T: Send + 'static>(
+        client: impl FnOnce(Connection) -> T + Send + 'static,
+    ) -> (Connection, String, thread::JoinHandle<T>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let handle = thread::spawn(move || {
+            let (connection, remote_name) = Connection::connect(&address, "white").unwrap();
+            assert_eq!(remote_name, "black");
+            client(connection)
+        });
+        let (connection, remote_name) = Connection::accept(&listener, "black").unwrap();
+        (connection, remote_name, handle)
+    }
+
+    fn f5() -> Vec2 {
+        record::parse_position("f5").unwrap()
+    }
+
+    // 처음의 국면에 검은 돌이 f5에 둔 뒤의 모눈판
+    fn after_f5() -> BitBoard {
+        let mut board = BitBoard::from_board(&Board::initial());
+        board.place(TurnEnum::Black, bitboard::to_square(f5()));
+        board
+    }
+
+    #[test]
+    fn exchange_hello_move_and_bye() {
+        let (mut host, remote_name, client) = connect_pair(|mut connection| {
+            let position = connection.receive_move(&Board::initial(), TurnEnum::Black);
+            connection.send(&Message::Bye).unwrap();
+            position.map(|position| position.to_string())
+        });
+        assert_eq!(remote_name, "white");
+        host.send(&Message::Move {
+            position: f5(),
+            board: after_f5(),
+        })
+        .unwrap();
+        assert!(matches!(host.receive(), Ok(Message::Bye)));
+        assert_eq!(client.join().unwrap(), Ok("f5".to_string()));
+    }
+
+    #[test]
+    fn illegal_move_is_rejected_with_error() {
+        let (mut host, _, client) = connect_pair(|mut connection| {
+            connection.receive_move(&Board::initial(), TurnEnum::Black)
+        });
+        // 처음의 국면에서 a1에는 놓을 수 없다
+        host.send(&Message::Move {
+            position: record::parse_position("a1").unwrap(),
+            board: after_f5(),
+        })
+        .unwrap();
+        assert!(matches!(host.receive(), Ok(Message::Error(_))));
+        assert!(client.join().unwrap().is_err());
+    }
+
+    #[test]
+    fn inconsistent_board_is_rejected_with_error() {
+        let (mut host, _, client) = connect_pair(|mut connection| {
+            connection.receive_move(&Board::initial(), TurnEnum::Black)
+        });
+        // 돌을 뒤집지 않은 모눈판을 함께 보낸다
+        host.send(&Message::Move {
+            position: f5(),
+            board: BitBoard::from_board(&Board::initial()),
+        })
+        .unwrap();
+        assert!(matches!(host.receive(), Ok(Message::Error(_))));
+        assert!(client.join().unwrap().is_err());
+    }
+
+    #[test]
+    fn dropped_peer_is_an_error() {
+        // 상대는 인사를 주고받은 뒤 아무것도 보내지 않고 끊는다
+        let (mut host, _, client) = connect_pair(drop);
+        client.join().unwrap();
+        assert!(host.receive().is_err());
+        assert!(
+            host.receive_move(&Board::initial(), TurnEnum::White)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn silent_peer_times_out() {
+        // 상대는 인사만 하고 접속을 연 채로 아무것도 보내지 않는다
+        let (silent, stop) = mpsc::channel::<()>();
+        let (mut host, _, client) = connect_pair(move |connection| {
+            let _ = stop.recv();
+            drop(connection);
+        });
+        host.reader
+            .get_ref()
+            .set_read_timeout(Some(Duration::from_millis(200)))
+            .unwrap();
+        let start = Instant::now();
+        let error = host
+            .receive_move(&Board::initial(), TurnEnum::White)
+            .err()
+            .unwrap();
+        assert!(error.contains("응답이 없어"), "{error}");
+        assert!(start.elapsed() < Duration::from_secs(5));
+        drop(silent);
+        client.join().unwrap();
+    }
+
+    #[test]
+    fn hello_is_required_first() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let client = thread::spawn(move || {
+            let mut connection = Connection::new(TcpStream::connect(address).unwrap()).unwrap();
+            connection.send(&Message::Bye).unwrap();
+        });
+        assert!(Connection::accept(&listener, "black").is_err());
+        client.join().unwrap();
+    }
+
+    #[test]
+    fn parse_messages() {
+        let line = Message::Move {
+            position: f5(),
+            board: after_f5(),
+        }
+        .to_line();
+        assert_eq!(line, "MOVE f5 0000003810000000 0000000008000000");
+        assert!(matches!(
+            Message::parse(&line),
+            Ok(Message::Move { position, board }) if position.to_string() == "f5" && board == after_f5()
+        ));
+        assert!(matches!(
+            Message::parse("HELLO reversi 1 Reversi Player"),
+            Ok(Message::Hello { version: 1, name }) if name == "Reversi Player"
+        ));
+        assert!(matches!(Message::parse("BYE"), Ok(Message::Bye)));
+        assert!(matches!(
+            Message::parse("ERROR bad move"),
+            Ok(Message::Error(reason)) if reason == "bad move"
+        ));
+    }
+
+    #[test]
+    fn malformed_lines_fail_to_parse() {
+        for line in [
+            "",
+            "PING",
+            "HELLO",
+            "HELLO chess 1 name",
+            "HELLO reversi one name",
+            "MOVE",
+            "MOVE f5",
+            "MOVE i9 0 0",
+            "MOVE f5 xyz 0",
+            "MOVE f5 0 0 0",
+            "MOVE f5 10000000000000000 0",
+            "BYE now",
+            "bye",
+        ] {
+            assert!(Message::parse(line).is_err(), "{line}");
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use std::time::Instant;
+
+use crate::TurnEnum;
+use crate::bitboard::{self, BitBoard};
+use crate::board::Board;
+
+// 처음의 국면에서 깊이 1, 2, ...까지 진행했을 때의 끝의 국면의 수로 알려진 값
+// 패스도 한 수로 세고, 도중에 승부가 난 국면은 그 자리에서 하나로 센다
+const KNOWN_COUNTS: [u64; 10] = [4, 12, 56, 244, 1396, 8200, 55092, 390216, 3005288, 24571284];
+
+// Board로 depth 수 앞까지의 끝의 국면을 센다
+fn perft_board(board: &Board, color: TurnEnum, depth: u32, passed: bool) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let mut board = *board;
+    let moves = board.get_legal_moves(color);
+    if moves.is_empty() {
+        return if passed {
+            1
+        } else {
+            perft_board(&board, color.get_opponent(), depth - 1, true)
+        };
+    }
+    moves
+        .into_iter()
+        .map(|position| {
+            let mut next = board;
+            next.place(color, position);
+            perft_board(&next, color.get_opponent(), depth - 1, false)
+        })
+        .sum()
+}
+
+// BitBoard로 depth 수 앞까지의 끝의 국면을 센다
+fn perft_bitboard(bitboard: &BitBoard, color: TurnEnum, depth: u32, passed: bool) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let moves = bitboard.get_legal_moves(color);
+    if moves == 0 {
+        return if passed {
+            1
+        } else {
+            perft_bitboard(bitboard, color.get_opponent(), depth - 1, true)
+        };
+    }
+    bitboard::squares(moves)
+        .map(|square| {
+            let mut next = *bitboard;
+            next.place(color, square);
+            perft_bitboard(&next, color.get_opponent(), depth - 1, false)
+        })
+        .sum()
+}
+
+// 두 구현을 같은 수순으로 진행시키며 놓을 수 있는 칸과 놓은 뒤의 국면이 모두 같은지 확인한다
+// 어긋나면 그 국면까지의 수순을 붙인 오류를 반환한다
+fn compare(
+    board: &Board,
+    color: TurnEnum,
+    depth: u32,
+    passed: bool,
+    line: &mut Vec<String>,
+) -> Result<(), String> {
+    let mut board = *board;
+    let bitboard = BitBoard::from_board(&board);
+    let error = |line: &[String], message: &str| {
+        format!(
+            "{}: {message}",
+            if line.is_empty() {
+                "처음의 국면".to_string()
+            } else {
+                line.concat()
+            }
+        )
+    };
+
+    let moves = board.get_legal_moves(color);
+    let expected = moves.iter().fold(0, |bits, position| {
+        bits | 1 << bitboard::to_square(*position)
+    });
+    if bitboard.get_legal_moves(color) != expected {
+        return Err(error(
+            line,
+            &format!(
+                "놓을 수 있는 칸이 다릅니다 (Board {expected:#018x}, BitBoard {:#018x})",
+                bitboard.get_legal_moves(color)
+            ),
+        ));
+    }
+    if depth == 0 || (moves.is_empty() && passed) {
+        return Ok(());
+    }
+    if moves.is_empty() {
+        line.push("--".to_string());
+        compare(&board, color.get_opponent(), depth - 1, true, line)?;
+        line.pop();
+        return Ok(());
+    }
+
+    for position in moves {
+        let mut next = board;
+        next.place(color, position);
+        let mut next_bitboard = bitboard;
+        next_bitboard.place(color, bitboard::to_square(position));
+        line.push(position.to_string());
+        if BitBoard::from_board(&next) != next_bitboard {
+            return Err(error(line, "놓은 뒤의 국면이 다릅니다"));
+        }
+        compare(&next, color.get_opponent(), depth - 1, false, line)?;
+        line.pop();
+    }
+    Ok(())
+}
+
+// 깊이 1부터 max_depth까지 두 구현의 결과를 비교하고 속도를 표시한다
+// 모두 일치하면 true를 반환한다
+pub fn run(max_depth: u32) -> bool {
+    let board = Board::initial();
+    let bitboard = BitBoard::from_board(&board);
+    println!("깊이  국면의 수      Board      BitBoard   결과");
+    for depth in 1..=max_depth {
+        if let Err(e) = compare(&board, TurnEnum::Black, depth, false, &mut Vec::new()) {
+            println!("{depth:>4}  {e}");
+            return false;
+        }
+
+        let start = Instant::now();
+        let board_count = perft_board(&board, TurnEnum::Black, depth, false);
+        let board_time = start.elapsed().as_secs_f64();
+        let start = Instant::now();
+        let bitboard_count = perft_bitboard(&bitboard, TurnEnum::Black, depth, false);
+        let bitboard_time = start.elapsed().as_secs_f64();
+
+        let known = KNOWN_COUNTS.get(depth as usize - 1);
+        let result = if board_count != bitboard_count {
+            "두 구현의 수가 다릅니다"
+        } else if known.is_some_and(|known| *known != board_count) {
+            "알려진 값과 다릅니다"
+        } else {
+            "일치"
+        };
+        println!(
+            "{depth:>4}  {board_count:>11}  {board_time:>8.3}초  {bitboard_time:>8.3}초   {result}"
+        );
+        if result != "일치" {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // board에서 color의 놓을 수 있는 칸과 뒤집히는 돌을 Board의 결과와 한 칸씩 비교한다
+    // BitBoard의 놓을 수 있는 칸을 반환한다
+    fn check_position(board: &Board, color: TurnEnum) -> u64 {
+        let mut board = *board;
+        let bitboard = BitBoard::from_board(&board);
+        let moves = bitboard.get_legal_moves(color);
+        for square in 0..64 {
+            let position = bitboard::to_position(square);
+            let can_place = board.check_can_place(color, position, false);
+            assert_eq!(moves >> square & 1 == 1, can_place, "{board} {position}");
+            if !can_place {
+                continue;
+            }
+            let mut next = board;
+            next.place(color, position);
+            let next_bitboard = BitBoard::from_board(&next);
+            // 놓기 전에 상대의 돌이었다가 자신의 돌이 된 칸이 뒤집힌 돌이다
+            let flipped = bitboard.get_own(color.get_opponent()) & next_bitboard.get_own(color);
+            assert_eq!(
+                bitboard.get_flips(color, square),
+                flipped,
+                "{board} {position}"
+            );
+            let mut placed = bitboard;
+            placed.place(color, square);
+            assert_eq!(placed, next_bitboard, "{board} {position}");
+        }
+        assert_eq!(moves != 0, board.check_can_place_all(color), "{board}");
+        moves
+    }
+
+    #[test]
+    fn perft_matches_known_counts() {
+        let board = Board::initial();
+        let bitboard = BitBoard::from_board(&board);
+        for depth in 1..=6 {
+            let known = KNOWN_COUNTS[depth as usize - 1];
+            assert_eq!(perft_board(&board, TurnEnum::Black, depth, false), known);
+            assert_eq!(
+                perft_bitboard(&bitboard, TurnEnum::Black, depth, false),
+                known
+            );
+        }
+    }
+
+    #[test]
+    fn compare_finds_no_difference() {
+        let board = Board::initial();
+        assert_eq!(
+            compare(&board, TurnEnum::Black, 5, false, &mut Vec::new()),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn masks_match_board_on_positions_with_passes() {
+        let positions = [
+            // 흰 돌은 놓을 수 있는 칸이 없다
+            "XO--------------------------------------------------------------",
+            // h1과 a2는 비트로는 이웃하지만 모눈판에서는 이어져 있지 않다. 검은 돌은 패스한다
+            "------XO--------------------------------------------------------",
+            // h8만 비어 있다. 흰 돌은 패스하고 검은 돌은 g8을 뒤집는다
+            "XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXO-",
+            // 흰 돌은 패스하고 검은 돌은 a1에 놓아 가로와 대각선의 두 방향을 뒤집는다
+            "-OOOOOOXOOOOOOOOOOXOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOX",
+            // 흰 돌이 모두 없어져 양쪽 모두 놓을 수 없이 끝난 국면
+            "XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX-",
+        ];
+        let mut passes = 0;
+        for text in positions {
+            let board = Board::parse(text).unwrap();
+            for color in [TurnEnum::Black, TurnEnum::White] {
+                if check_position(&board, color) == 0 {
+                    passes += 1;
+                }
+            }
+        }
+        assert_eq!(passes, 6);
+    }
+
+    #[test]
+    fn masks_match_board_through_whole_games() {
+        // 수의 고르는 방법을 바꿔 가며 끝까지 두고, 패스하는 국면도 포함하여 모든 국면을 비교한다
+        let mut passes = 0;
+        for seed in 1..=40 {
+            let mut board = Board::initial();
+            let mut color = TurnEnum::Black;
+            let mut passed = false;
+            for ply in 0.. {
+                let moves = check_position(&board, color);
+                check_position(&board, color.get_opponent());
+                if moves == 0 {
+                    if passed {
+                        break;
+                    }
+                    passes += 1;
+                    passed = true;
+                } else {
+                    let squares: Vec<u32> = bitboard::squares(moves).collect();
+                    let square = squares[(ply * seed) % squares.len()];
+                    board.place(color, bitboard::to_position(square));
+                    passed = false;
+                }
+                color = color.get_opponent();
+            }
+        }
+        assert!(passes > 0);
+    }
+}
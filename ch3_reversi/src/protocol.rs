@@ -0,0 +1,453 @@
+use std::io::{self, BufRead, Write};
+use std::time::Duration;
+
+use rand::rngs::ThreadRng;
+
+use crate::board::Board;
+use crate::book::Book;
+use crate::history::History;
+use crate::record::{self, Record};
+use crate::{BOARD_HEIGHT, BOARD_WIDTH, LevelEnum, TurnEnum, Vec2, ai};
+
+// 엔진 모드가 아는 명령
+//   protocol_version, name, version, known_command 명령, list_commands, quit
+//   boardsize 8                 모눈판의 크기. 8만 받는다
+//   clear_board                 처음의 국면으로 한다
+//   set_position 64문자          a1, b1, ..., h8의 순으로 X(검은 돌) O(흰 돌) -(빈칸)을 늘어놓은 국면으로 한다
+//   set_moves f5d6c3...         처음의 국면부터 기보대로 둔 국면으로 한다
+//   play 색 수                   색이 수를 둔다. 놓을 수 있는 칸이 없으면 수 대신 pass
+//   genmove 색                   색의 수를 컴퓨터가 골라서 두고 그 수를 반환한다
+//   undo                        마지막으로 둔 수를 되돌린다
+//   level 강함                   random, easy, normal, hard
+//   set_time 초                  한 수에 쓰는 시간. 0이면 강함의 기본값
+//   legal_moves 색               놓을 수 있는 칸을 모두 반환한다
+//   showboard, final_score
+const COMMANDS: [&str; 18] = [
+    "protocol_version",
+    "name",
+    "version",
+    "known_command",
+    "list_commands",
+    "quit",
+    "boardsize",
+    "clear_board",
+    "set_position",
+    "set_moves",
+    "play",
+    "genmove",
+    "undo",
+    "level",
+    "set_time",
+    "legal_moves",
+    "showboard",
+    "final_score",
+];
+
+// b, black, w, white를 돌의 색으로 바꾼다
+fn parse_color(text: Option<&&str>) -> Result<TurnEnum, String> {
+    match text.map(|text| text.to_ascii_lowercase()).as_deref() {
+        Some("b" | "black") => Ok(TurnEnum::Black),
+        Some("w" | "white") => Ok(TurnEnum::White),
+        _ => Err("syntax error".to_string()),
+    }
+}
+
+// 외부의 프로그램에서 조작되는 AI
+struct Engine {
+    board: Board,
+    history: History,
+    level: LevelEnum,
+    time: Option<Duration>, // 한 수에 쓰는 시간. None이면 강함의 기본값
+    book: Option<Book>,
+    rng: ThreadRng,
+}
+
+impl Engine {
+    fn new(level: LevelEnum, book: Option<Book>) -> Self {
+        Self {
+            board: Board::initial(),
+            history: History::new(),
+            level,
+            time: None,
+            book,
+            rng: rand::rng(),
+        }
+    }
+
+    // 명령을 하나 실행하고 응답의 본문을 반환한다
+    fn execute(&mut self, command: &str, args: &[&str]) -> Result<String, String> {
+        match command {
+            "protocol_version" => Ok("2".to_string()),
+            "name" => Ok(env!("CARGO_PKG_NAME").to_string()),
+            "version" => Ok(env!("CARGO_PKG_VERSION").to_string()),
+            "known_command" => Ok(args
+                .first()
+                .is_some_and(|name| COMMANDS.contains(name))
+                .to_string()),
+            "list_commands" => Ok(COMMANDS.join("\n")),
+            "quit" => Ok(String::new()),
+            "boardsize" => match args.first().and_then(|size| size.parse().ok()) {
+                Some(BOARD_WIDTH) => Ok(String::new()),
+                _ => Err("unacceptable size".to_string()),
+            },
+            "clear_board" => {
+                self.board = Board::initial();
+                self.history.clear();
+                Ok(String::new())
+            }
+            "set_position" => {
+                self.board = Board::parse(args.first().copied().unwrap_or_default())?;
+                self.history.clear();
+                Ok(String::new())
+            }
+            "set_moves" => {
+                let record = Record::parse(&args.concat())?;
+                self.board = Board::initial();
+                self.history.clear();
+                for (color, position) in record.replay()?.into_iter().zip(record.moves) {
+                    self.play(color, position)?;
+                }
+                Ok(String::new())
+            }
+            "play" => {
+                let color = parse_color(args.first())?;
+                let text = args.get(1).ok_or("syntax error")?;
+                if text.eq_ignore_ascii_case("pass") {
+                    // 놓을 수 있는 칸이 있을 때는 패스할 수 없다
+                    if self.board.check_can_place_all(color) {
+                        return Err("illegal move".to_string());
+                    }
+                    return Ok(String::new());
+                }
+                let position = record::parse_position(text).ok_or("syntax error")?;
+                self.play(color, position)?;
+                Ok(String::new())
+            }
+            "genmove" => {
+                let color = parse_color(args.first())?;
+                let Some(result) = ai::choose_move(
+                    &self.board,
+                    color,
+                    self.level,
+                    self.time,
+                    self.book.as_ref(),
+                    &mut self.rng,
+                ) else {
+                    return Ok("pass".to_string());
+                };
+                // 고른 근거는 표준 오류 출력에 써서 응답과 섞이지 않게 한다
+                if result.from_book {
+                    eprintln!("{}  정석", result.position);
+                } else {
+                    eprintln!(
+                        "{}  깊이 {}  평가 {:+}  {}국면",
+                        result.position, result.depth, result.score, result.nodes
+                    );
+                }
+                self.play(color, result.position)?;
+                Ok(result.position.to_string())
+            }
+            "undo" => {
+                let last = self.history.undo().ok_or("cannot undo")?;
+                self.board = last.board;
+                Ok(String::new())
+            }
+            "level" => {
+                self.level = LevelEnum::parse(args.first().copied().unwrap_or_default())?;
+                Ok(String::new())
+            }
+            "set_time" => {
+                let seconds: f64 = args
+                    .first()
+                    .and_then(|seconds| seconds.parse().ok())
+                    .filter(|seconds: &f64| seconds.is_finite() && *seconds >= 0.0)
+                    .ok_or("syntax error")?;
+                self.time = (seconds > 0.0).then(|| Duration::from_secs_f64(seconds));
+                Ok(String::new())
+            }
+            "legal_moves" => {
+                let color = parse_color(args.first())?;
+                let moves: Vec<String> = self
+                    .board
+                    .get_legal_moves(color)
+                    .iter()
+                    .map(Vec2::to_string)
+                    .collect();
+                Ok(moves.join(" "))
+            }
+            "showboard" => Ok(self.to_text()),
+            "final_score" => {
+                let difference = self.board.get_disk_count(TurnEnum::Black) as i32
+                    - self.board.get_disk_count(TurnEnum::White) as i32;
+                Ok(match difference {
+                    0 => "0".to_string(),
+                    _ if difference > 0 => format!("B+{difference}"),
+                    _ => format!("W+{}", -difference),
+                })
+            }
+            _ => Err("unknown command".to_string()),
+        }
+    }
+
+    // color가 position에 두고 되돌릴 수 있도록 기록한다
+    fn play(&mut self, color: TurnEnum, position: Vec2) -> Result<(), String> {
+        if !self.board.check_can_place(color, position, false) {
+            return Err("illegal move".to_string());
+        }
+        self.history.push(self.board, color, position);
+        self.board.place(color, position);
+        Ok(())
+    }
+
+    // showboard가 반환하는 모눈판의 그림
+    fn to_text(&self) -> String {
+        let mut text = String::from("\n  a b c d e f g h");
+        for y in 0..BOARD_HEIGHT {
+            text += &format!("\n{}", y + 1);
+            for x in 0..BOARD_WIDTH {
+                text += match self.board.get(x as i8, y as i8) {
+                    TurnEnum::Black => " X",
+                    TurnEnum::White => " O",
+                    _ => " -",
+                };
+            }
+        }
+        text + &format!(
+            "\nX {}  O {}",
+            self.board.get_disk_count(TurnEnum::Black),
+            self.board.get_disk_count(TurnEnum::White)
+        )
+    }
+}
+
+// 엔진 모드를 실행한다. GTP(Go Text Protocol)를 본뜬 행 단위의 프로토콜로 표준 입출력을 통해 명령을 받는다
+//   요청: [번호] 명령 [인수...]      '#' 이후는 코멘트로서 무시한다
+//   응답: =[번호] 결과  또는  ?[번호] 오류  뒤에 빈 행을 하나 둔다
+// quit 또는 입력의 끝에서 끝난다
+pub fn run(level: LevelEnum, book: Option<Book>) {
+    let mut engine = Engine::new(level, book);
+    serve(&mut engine, io::stdin().lock(), &mut io::stdout());
+}
+
+// input에서 명령을 한 행씩 읽어 실행하고 응답을 output에 쓴다
+fn serve(engine: &mut Engine, input: impl BufRead, output: &mut impl Write) {
+    for line in input.lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        let line = line.split('#').next().unwrap_or_default();
+        let mut words = line.split_whitespace();
+        let Some(mut command) = words.next() else {
+            continue;
+        };
+
+        // 앞에 붙은 번호는 응답에 그대로 붙여 돌려준다
+        let id = command.parse::<u32>().ok();
+        if id.is_some() {
+            let Some(next) = words.next() else {
+                continue;
+            };
+            command = next;
+        }
+        let id = id.map_or(String::new(), |id| id.to_string());
+        let args: Vec<&str> = words.collect();
+
+        let response = match engine.execute(command, &args) {
+            Ok(text) => format!("={id} {text}"),
+            Err(e) => format!("?{id} {e}"),
+        };
+        if writeln!(output, "{}\n", response.trim_end()).is_err() || output.flush().is_err() {
+            break;
+        }
+        if command == "quit" {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 처음의 국면에서 검은 돌이 놓을 수 있는 칸
+    const OPENING_MOVES: [&str; 4] = ["d3", "c4", "f5", "e6"];
+
+    // 흰 돌만 놓을 수 있는 국면. 검은 돌은 패스해야 한다
+    const BLACK_MUST_PASS: &str =
+        "------XO--------------------------------------------------------";
+
+    // script의 명령을 순서대로 실행하고 응답을 하나씩 나누어 반환한다
+    fn respond(engine: &mut Engine, script: &str) -> Vec<String> {
+        let mut output = Vec::new();
+        serve(engine, script.as_bytes(), &mut output);
+        let output = String::from_utf8(output).unwrap();
+        // 응답은 빈 행으로 끝난다
+        assert!(output.is_empty() || output.ends_with("\n\n"), "{output:?}");
+        output
+            .split_terminator("\n\n")
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn new_engine() -> Engine {
+        Engine::new(LevelEnum::Easy, None)
+    }
+
+    #[test]
+    fn numbered_ids_are_echoed() {
+        let mut engine = new_engine();
+        let responses = respond(
+            &mut engine,
+            "1 protocol_version\n\n# 코멘트만의 행\n2 frobnicate\nname\n7 boardsize 9\n",
+        );
+        assert_eq!(
+            responses,
+            [
+                "=1 2",
+                "?2 unknown command",
+                "= ch3_reversi",
+                "?7 unacceptable size"
+            ]
+        );
+    }
+
+    #[test]
+    fn known_and_unknown_commands() {
+        let mut engine = new_engine();
+        let responses = respond(
+            &mut engine,
+            "known_command genmove\nknown_command frobnicate\nfrobnicate b\n",
+        );
+        assert_eq!(responses, ["= true", "= false", "? unknown command"]);
+        let list = &respond(&mut engine, "list_commands")[0];
+        assert_eq!(list.lines().count(), COMMANDS.len());
+    }
+
+    #[test]
+    fn play_legal_illegal_and_pass() {
+        let mut engine = new_engine();
+        let responses = respond(
+            &mut engine,
+            "play b f5  # 코멘트는 무시한다\nplay w f5\nplay w a1\nplay b pass\nplay x d6\nplay w z9\nfinal_score\n",
+        );
+        assert_eq!(
+            responses,
+            [
+                "=",
+                "? illegal move",
+                "? illegal move",
+                // 놓을 수 있는 칸이 있을 때는 패스할 수 없다
+                "? illegal move",
+                "? syntax error",
+                "? syntax error",
+                "= B+3"
+            ]
+        );
+
+        let responses = respond(
+            &mut engine,
+            &format!("set_position {BLACK_MUST_PASS}\nlegal_moves b\nplay b pass\nlegal_moves w\n"),
+        );
+        assert_eq!(responses, ["=", "=", "=", "= f1"]);
+    }
+
+    #[test]
+    fn set_position_and_showboard() {
+        let mut engine = new_engine();
+        let responses = respond(
+            &mut engine,
+            &format!("set_position {BLACK_MUST_PASS}\nshowboard\n"),
+        );
+        assert_eq!(responses[0], "=");
+        let board: Vec<&str> = responses[1].lines().collect();
+        assert_eq!(board[1], "  a b c d e f g h");
+        assert_eq!(board[2], "1 - - - - - - X O");
+        assert_eq!(board[10], "X 1  O 1");
+
+        let responses = respond(
+            &mut engine,
+            "set_position XO\nset_moves f5d6\nlegal_moves b\n",
+        );
+        assert!(responses[0].starts_with('?'));
+        assert_eq!(responses[1], "=");
+        assert_eq!(responses[2], "= c3 c4 c5 c6 c7");
+    }
+
+    #[test]
+    fn genmove_returns_legal_move_or_pass() {
+        for level in ["random", "easy", "normal", "hard"] {
+            let mut engine = new_engine();
+            let responses = respond(
+                &mut engine,
+                &format!("level {level}\nset_time 0.05\ngenmove b\n"),
+            );
+            assert_eq!(responses[..2], ["=", "="]);
+            let position = responses[2].strip_prefix("= ").unwrap();
+            assert!(OPENING_MOVES.contains(&position), "{level} {position}");
+            // 고른 수는 모눈판에 두어진다
+            assert_eq!(respond(&mut engine, "final_score"), ["= B+3"]);
+        }
+
+        let mut engine = new_engine();
+        let responses = respond(
+            &mut engine,
+            &format!("set_position {BLACK_MUST_PASS}\ngenmove b\n"),
+        );
+        assert_eq!(responses, ["=", "= pass"]);
+    }
+
+    #[test]
+    fn undo_restores_the_previous_position() {
+        let mut engine = new_engine();
+        let responses = respond(
+            &mut engine,
+            "undo\nplay b f5\nplay w d6\nundo\nfinal_score\nundo\nfinal_score\nundo\n",
+        );
+        assert_eq!(
+            responses,
+            [
+                "? cannot undo",
+                "=",
+                "=",
+                "=",
+                "= B+3",
+                "=",
+                "= 0",
+                "? cannot undo"
+            ]
+        );
+        // set_position으로 바꾼 국면은 되돌릴 수 없다
+        let responses = respond(&mut engine, "play b f5\nclear_board\nundo\n");
+        assert_eq!(responses, ["=", "=", "? cannot undo"]);
+    }
+
+    #[test]
+    fn set_time_accepts_only_non_negative_seconds() {
+        let mut engine = new_engine();
+        let responses = respond(
+            &mut engine,
+            "set_time 1.5\nset_time 0\nset_time -1\nset_time abc\nset_time inf\nset_time\n",
+        );
+        assert_eq!(
+            responses,
+            [
+                "=",
+                "=",
+                "? syntax error",
+                "? syntax error",
+                "? syntax error",
+                "? syntax error"
+            ]
+        );
+        respond(&mut engine, "set_time 2");
+        assert_eq!(engine.time, Some(Duration::from_secs(2)));
+        respond(&mut engine, "set_time 0");
+        assert_eq!(engine.time, None);
+    }
+
+    #[test]
+    fn quit_stops_reading() {
+        let mut engine = new_engine();
+        assert_eq!(respond(&mut engine, "3 quit\nname\n"), ["=3"]);
+    }
+}
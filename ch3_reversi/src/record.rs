@@ -0,0 +1,218 @@
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::board::Board;
+use crate::{TurnEnum, Vec2};
+
+// 기보. 파일에는 다음과 같이 쓴다
+//   Black: 검은 돌의 대국자
+//   White: 흰 돌의 대국자
+//   Date: 2026-10-19
+//   Result: 36-28        (검은 돌과 흰 돌의 개수. 진행 중이면 *)
+//   f5d6c3d3c4f4f6f3e6e7 ...
+// 수는 열 a~h와 행 1~8을 이어서 쓰고, 패스는 쓰지 않는다. 대문자와 공백, 줄바꿈도 읽을 수 있다
+pub struct Record {
+    pub black: String,
+    pub white: String,
+    pub date: String,
+    pub result: String,
+    pub moves: Vec<Vec2>,
+}
+
+// 「f5」처럼 쓴 칸을 좌표로 바꾼다
+pub fn parse_position(text: &str) -> Option<Vec2> {
+    let [column, row] = text.as_bytes() else {
+        return None;
+    };
+    let position = Vec2 {
+        x: column.to_ascii_lowercase().checked_sub(b'a')? as i8,
+        y: row.checked_sub(b'1')? as i8,
+    };
+    position.is_valid().then_some(position)
+}
+
+// 지금의 날짜(2026-10-19)와 시각(15:30:00)을 협정 세계시로 반환한다
+pub fn get_date_time() -> (String, String) {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs()) as i64;
+    to_date_time(seconds)
+}
+
+// 1970-01-01 00:00:00부터의 초 수를 협정 세계시의 날짜와 시각으로 바꾼다
+fn to_date_time(seconds: i64) -> (String, String) {
+    let time = seconds.rem_euclid(86_400);
+
+    // 1970-01-01부터의 날수를 그레고리력의 연월일로 바꾼다
+    let z = seconds.div_euclid(86_400) + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (
+        format!("{year:04}-{month:02}-{day:02}"),
+        format!("{:02}:{:02}:{:02}", time / 3600, time / 60 % 60, time % 60),
+    )
+}
+
+impl Record {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut record = Self {
+            black: String::new(),
+            white: String::new(),
+            date: String::new(),
+            result: "*".to_string(),
+            moves: Vec::new(),
+        };
+        let mut move_text = String::new();
+        for line in text.lines().map(str::trim) {
+            match line.split_once(':') {
+                Some((key, value)) => {
+                    let value = value.trim().to_string();
+                    match key.trim() {
+                        "Black" => record.black = value,
+                        "White" => record.white = value,
+                        "Date" => record.date = value,
+                        "Result" => record.result = value,
+                        _ => {} // 모르는 항목은 무시한다
+                    }
+                }
+                None => move_text.extend(line.chars().filter(|c| !c.is_whitespace())),
+            }
+        }
+
+        let chars: Vec<char> = move_text.chars().collect();
+        for (i, pair) in chars.chunks(2).enumerate() {
+            let text: String = pair.iter().collect();
+            let position = parse_position(&text)
+                .ok_or_else(|| format!("{}수째를 해석할 수 없습니다: {text}", i + 1))?;
+            record.moves.push(position);
+        }
+
+        // 처음부터 끝까지 놓을 수 있는 수인지 확인한다
+        record.replay()?;
+        Ok(record)
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text =
+            fs::read_to_string(path).map_err(|e| format!("{path}을(를) 읽을 수 없습니다: {e}"))?;
+        Self::parse(&text).map_err(|e| format!("{path}: {e}"))
+    }
+
+    pub fn to_text(&self) -> String {
+        let moves: String = self.moves.iter().map(Vec2::to_string).collect();
+        format!(
+            "Black: {}\nWhite: {}\nDate: {}\nResult: {}\n{moves}\n",
+            self.black, self.white, self.date, self.result
+        )
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.to_text()).map_err(|e| format!("{path}에 쓸 수 없습니다: {e}"))
+    }
+
+    // 처음의 국면부터 기보대로 두어 각 수를 둔 쪽의 색을 반환한다
+    // 놓을 수 있는 칸이 없는 쪽은 패스한 것으로 하고, 놓을 수 없는 수가 있으면 오류를 반환한다
+    pub fn replay(&self) -> Result<Vec<TurnEnum>, String> {
+        let mut board = Board::initial();
+        let mut turn = TurnEnum::Black;
+        let mut colors = Vec::new();
+        for (i, position) in self.moves.iter().enumerate() {
+            if !board.check_can_place_all(turn) {
+                turn = turn.get_opponent();
+            }
+            if !board.check_can_place(turn, *position, false) {
+                return Err(format!("{}수째의 {position}에는 놓을 수 없습니다", i + 1));
+            }
+            board.place(turn, *position);
+            colors.push(turn);
+            turn = turn.get_opponent();
+        }
+        Ok(colors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn moves(record: &Record) -> String {
+        record.moves.iter().map(Vec2::to_string).collect()
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let text = "Black: 사람\nWhite: 컴퓨터 (normal)\nDate: 2026-10-19\nResult: *\nf5d6c3d3c4\n";
+        let record = Record::parse(text).unwrap();
+        assert_eq!(record.black, "사람");
+        assert_eq!(record.white, "컴퓨터 (normal)");
+        assert_eq!(record.date, "2026-10-19");
+        assert_eq!(record.result, "*");
+        assert_eq!(moves(&record), "f5d6c3d3c4");
+        assert_eq!(record.to_text(), text);
+
+        // 대문자, 공백, 줄바꿈, 모르는 항목이 섞여 있어도 읽을 수 있다
+        let record = Record::parse("Event: 연습\n Result : 36-28 \nF5 D6\nc3\n").unwrap();
+        assert_eq!(record.result, "36-28");
+        assert_eq!(record.black, "");
+        assert_eq!(moves(&record), "f5d6c3");
+        let reparsed = Record::parse(&record.to_text()).unwrap();
+        assert_eq!(reparsed.to_text(), record.to_text());
+    }
+
+    #[test]
+    fn replay_with_a_forced_pass() {
+        // 8수째의 c1 뒤에 검은 돌은 놓을 수 있는 칸이 없으므로 흰 돌이 이어서 e3에 둔다
+        let record = Record::parse("d3c3b3b2f5a3a1c1e3").unwrap();
+        let colors: String = record
+            .replay()
+            .unwrap()
+            .iter()
+            .map(|color| if *color == TurnEnum::Black { 'X' } else { 'O' })
+            .collect();
+        assert_eq!(colors, "XOXOXOXOO");
+    }
+
+    #[test]
+    fn reject_illegal_and_unparseable_moves() {
+        let error = Record::parse("f5d6a1").err().unwrap();
+        assert!(error.contains("3수째"), "{error}");
+        let error = Record::parse("f5z9").err().unwrap();
+        assert!(error.contains("2수째를 해석할 수 없습니다: z9"), "{error}");
+        assert!(Record::parse("f5d").is_err());
+        // 검은 돌이 패스해야 할 때 검은 돌의 칸으로 보이는 수는 흰 돌이 두는 것이 된다
+        assert!(Record::parse("d3c3b3b2f5a3a1c1f4").is_err());
+
+        assert_eq!(parse_position("a1").map(|p| (p.x, p.y)), Some((0, 0)));
+        assert_eq!(parse_position("H8").map(|p| (p.x, p.y)), Some((7, 7)));
+        for text in ["", "a", "a0", "a9", "i1", "1a", "a10"] {
+            assert!(parse_position(text).is_none(), "{text}");
+        }
+    }
+
+    #[test]
+    fn date_time_at_known_epochs() {
+        for (seconds, date, time) in [
+            (0, "1970-01-01", "00:00:00"),
+            (-1, "1969-12-31", "23:59:59"),
+            (951_782_400, "2000-02-29", "00:00:00"),
+            (1_709_251_199, "2024-02-29", "23:59:59"),
+            (1_709_251_200, "2024-03-01", "00:00:00"),
+            (4_107_542_400, "2100-03-01", "00:00:00"),
+            (1_792_423_800, "2026-10-19", "15:30:00"),
+        ] {
+            assert_eq!(
+                to_date_time(seconds),
+                (date.to_string(), time.to_string()),
+                "{seconds}"
+            );
+        }
+    }
+}
@@ -1,10 +1,12 @@
+use std::collections::VecDeque;
 use std::convert::TryFrom;
 use std::convert::TryInto;
+use std::io::Write;
 
 use getch_rs::Getch;
 use getch_rs::Key;
-use rand::Rng;
-use rand::rngs::ThreadRng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
 // [2]상수를 정의하는 곳
 const SPELL_COST: i64 = 3;
@@ -66,7 +68,7 @@ impl CommandEnum {
 }
 
 // [3-4]맵의 종류를 정의한다
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 enum MapEnum {
     Field = 0,
     KingCastle,
@@ -104,19 +106,110 @@ impl Default for Character {
     }
 }
 
-struct Context {
+// [4-2]키 입력을 가져오는 곳을 선언한다
+trait InputSource {
+    // 다음 키를 반환한다. 더 이상 입력이 없으면 None을 반환한다
+    fn next_key(&mut self) -> Option<Key>;
+}
+
+impl InputSource for Getch {
+    fn next_key(&mut self) -> Option<Key> {
+        self.getch().ok()
+    }
+}
+
+// [4-3]미리 정해 둔 키를 차례로 입력하는 입력원을 선언한다
+struct ScriptedInput {
+    keys: VecDeque<Key>,
+}
+
+impl ScriptedInput {
+    // 문자는 그대로 Key::Char로, ESC 문자(\x1b)는 Key::Esc로 변환한다
+    pub fn new(script: &str) -> Self {
+        Self {
+            keys: script
+                .chars()
+                .map(|c| if c == '\x1b' { Key::Esc } else { Key::Char(c) })
+                .collect(),
+        }
+    }
+}
+
+impl InputSource for ScriptedInput {
+    fn next_key(&mut self) -> Option<Key> {
+        self.keys.pop_front()
+    }
+}
+
+// [4-4]화면 출력을 받는 곳을 선언한다
+trait OutputSink: Write {
+    // 화면을 클리어한다
+    fn clear(&mut self);
+}
+
+// [4-5]실제 터미널에 출력하는 출력처를 선언한다
+struct Terminal;
+
+impl Write for Terminal {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        std::io::stdout().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        std::io::stdout().flush()
+    }
+}
+
+impl OutputSink for Terminal {
+    fn clear(&mut self) {
+        clearscreen::clear().unwrap();
+    }
+}
+
+// [4-6]화면을 클리어할 때마다 새 프레임을 시작하여 그려진 내용을 기록하는 출력처를 선언한다
+#[derive(Default)]
+struct FrameRecorder {
+    frames: Vec<String>,
+}
+
+impl Write for FrameRecorder {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.frames.is_empty() {
+            self.frames.push(String::new());
+        }
+        self.frames
+            .last_mut()
+            .unwrap()
+            .push_str(&String::from_utf8_lossy(buf));
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl OutputSink for FrameRecorder {
+    fn clear(&mut self) {
+        self.frames.push(String::new());
+    }
+}
+
+struct Context<I: InputSource, O: OutputSink> {
     monsters: [Character; MonsterEnum::Max as usize],
     characters: [Character; CharacterEnum::Max as usize],
     map: [u8; MapEnum::Max as usize * MAP_HEIGHT * MAP_WIDTH],
     current_map: MapEnum,
     player_x: usize,
     player_y: usize,
-    rng: ThreadRng,
-    g: Getch,
+    rng: StdRng,
+    input: I,
+    output: O,
+    quit: bool, // ESC가 눌렸거나 입력이 끝났는지 여부
 }
 
-impl Context {
-    pub fn new() -> Self {
+impl<I: InputSource, O: OutputSink> Context<I, O> {
+    pub fn new(input: I, output: O, rng: StdRng) -> Self {
         // [5-4]맵의 지형 데이터를 선언한다
         let mut map = [0_u8; MapEnum::Max as usize * MAP_HEIGHT * MAP_WIDTH];
 
@@ -228,8 +321,10 @@ impl Context {
             // [6-1-3]플레이어의 좌표를 초기화한다
             player_x: 4,
             player_y: 6,
-            rng: rand::rng(),
-            g: Getch::new(),
+            rng,
+            input,
+            output,
+            quit: false,
         }
     }
 
@@ -247,35 +342,49 @@ impl Context {
     }
 
     // [6-2]전투 장면의 화면을 그리는 함수를 선언한다
-    fn draw_battle_screen(&self) {
+    fn draw_battle_screen(&mut self) {
         // [6-2-1]화면을 클리어한다
-        clearscreen::clear().unwrap();
+        self.output.clear();
 
         // [6-2-2]플레이어의 이름을 표시한다
-        println!("{}", self.characters[CharacterEnum::Player as usize].name);
+        writeln!(
+            self.output,
+            "{}",
+            self.characters[CharacterEnum::Player as usize].name
+        )
+        .unwrap();
 
         // [6-2-3]플레이어의 상태를 표시한다
-        println!(
+        writeln!(
+            self.output,
             "HP:{}/{} MP:{}/{}",
             self.characters[CharacterEnum::Player as usize].hp,
             self.characters[CharacterEnum::Player as usize].max_hp,
             self.characters[CharacterEnum::Player as usize].mp,
             self.characters[CharacterEnum::Player as usize].max_mp
-        );
+        )
+        .unwrap();
 
-        println!();
+        writeln!(self.output).unwrap();
 
         // [6-2-5]몬스터의 아스키아트를 그린다
-        print!("{}", self.characters[CharacterEnum::Monster as usize].aa);
+        write!(
+            self.output,
+            "{}",
+            self.characters[CharacterEnum::Monster as usize].aa
+        )
+        .unwrap();
 
         // [6-2-6]몬스터의 HP를 표시한다
-        println!(
+        writeln!(
+            self.output,
             "(HP:{}/{})",
             self.characters[CharacterEnum::Monster as usize].hp,
             self.characters[CharacterEnum::Monster as usize].max_hp,
-        );
+        )
+        .unwrap();
 
-        println!();
+        writeln!(self.output).unwrap();
     }
 
     // [6-3]명령을 선택하는 함수를 선언한다
@@ -292,26 +401,29 @@ impl Context {
             // [6-3-4]명령 목록을 표시한다
             for (i, command_name) in COMMAND_NAMES.iter().enumerate() {
                 if i == self.characters[CharacterEnum::Player as usize].command as usize {
-                    print!(">");
+                    write!(self.output, ">").unwrap();
                 } else {
-                    print!(" ");
+                    write!(self.output, " ").unwrap();
                 }
-                println!("{}", command_name);
+                writeln!(self.output, "{}", command_name).unwrap();
             }
 
             // [6-3-10]입력된 키에 따라 분기한다
-            match self.g.getch() {
-                Ok(Key::Char('w')) => {
+            match self.input.next_key() {
+                Some(Key::Char('w')) => {
                     self.characters[CharacterEnum::Player as usize]
                         .command
                         .decrease();
                 }
-                Ok(Key::Char('s')) => {
+                Some(Key::Char('s')) => {
                     self.characters[CharacterEnum::Player as usize]
                         .command
                         .increase();
                 }
-                Ok(Key::Esc) => std::process::exit(0),
+                Some(Key::Esc) | None => {
+                    self.quit = true;
+                    return;
+                }
                 _ => {
                     return;
                 }
@@ -334,17 +446,24 @@ impl Context {
         self.draw_battle_screen();
 
         // [6-4-5]전투 장면의 첫 메시지를 표시한다
-        println!(
+        writeln!(
+            self.output,
             "{}이(가) 나타났다!",
             self.characters[CharacterEnum::Monster as usize].name
-        );
+        )
+        .unwrap();
 
-        let _ = self.g.getch();
+        self.wait_key();
 
         // [6-4-7]전투가 끝날 때까지 루프한다
         loop {
             self.select_command();
 
+            // 게임이 종료되었다면 전투를 빠져나간다
+            if self.quit {
+                return;
+            }
+
             // [6-4-9]각 캐릭터를 반복한다
             for i in 0..CharacterEnum::Max as usize {
                 self.draw_battle_screen();
@@ -354,9 +473,9 @@ impl Context {
                 // [6-4-11]선택된 명령에 따라 분기한다
                 match self.characters[i].command {
                     CommandEnum::Fight => {
-                        println!("{}의 공격", self.characters[i].name);
+                        writeln!(self.output, "{}의 공격", self.characters[i].name).unwrap();
 
-                        let _ = self.g.getch();
+                        self.wait_key();
 
                         // [6-4-15]적에게 주는 대미지를 계산한다
                         let attack = self.characters[i].attack;
@@ -373,19 +492,21 @@ impl Context {
                         self.draw_battle_screen();
 
                         // [6-4-20]적에게 대미지를 준 메시지를 표시한다
-                        println!(
+                        writeln!(
+                            self.output,
                             "{}에게 {}의 데미지!",
                             self.characters[target as usize].name, damage
-                        );
+                        )
+                        .unwrap();
 
-                        let _ = self.g.getch();
+                        self.wait_key();
                     }
                     CommandEnum::Spell => {
                         // [6-4-23]MP가 충분한지 여부를 판정한다
                         if self.characters[i].mp < SPELL_COST {
-                            println!("MP가 부족하다.");
+                            writeln!(self.output, "MP가 부족하다.").unwrap();
 
-                            let _ = self.g.getch();
+                            self.wait_key();
                             continue;
                         }
 
@@ -395,9 +516,14 @@ impl Context {
                         self.draw_battle_screen();
 
                         // [6-4-29]주문을 외운 메시지를 표시한다
-                        println!("{}은(는) 주문을 외웠다.", self.characters[i].name);
+                        writeln!(
+                            self.output,
+                            "{}은(는) 주문을 외웠다.",
+                            self.characters[i].name
+                        )
+                        .unwrap();
 
-                        let _ = self.g.getch();
+                        self.wait_key();
 
                         // [6-4-31]HP를 회복시킨다
                         self.characters[i].hp = self.characters[i].max_hp;
@@ -405,15 +531,21 @@ impl Context {
                         self.draw_battle_screen();
 
                         // [6-4-33]HP가 회복된 메시지를 표시한다
-                        println!("{}의 상처가 회복되었다.", self.characters[i].name);
-
-                        let _ = self.g.getch();
+                        writeln!(
+                            self.output,
+                            "{}의 상처가 회복되었다.",
+                            self.characters[i].name
+                        )
+                        .unwrap();
+
+                        self.wait_key();
                     }
                     CommandEnum::Run => {
                         // [6-4-36]도망친 메시지를 표시한다
-                        println!("{}은(는) 도망쳤다.", self.characters[i].name);
+                        writeln!(self.output, "{}은(는) 도망쳤다.", self.characters[i].name)
+                            .unwrap();
 
-                        let _ = self.g.getch();
+                        self.wait_key();
                         return;
                     }
                     CommandEnum::Max => {}
@@ -424,7 +556,7 @@ impl Context {
                     match target {
                         CharacterEnum::Player => {
                             // [6-4-42]플레이어가 사망한 메시지를 표시한다
-                            println!("당신은 사망했습니다.",);
+                            writeln!(self.output, "당신은 사망했습니다.",).unwrap();
                         }
                         CharacterEnum::Monster => {
                             // [6-4-44]몬스터의 아스키아트를 아무것도 표시하지 않게 다시 작성한다
@@ -433,14 +565,16 @@ impl Context {
                             self.draw_battle_screen();
 
                             // [6-4-46]몬스터를 쓰러뜨린 메시지를 표시한다
-                            println!(
+                            writeln!(
+                                self.output,
                                 "{}을(를) 쓰러뜨렸다!",
                                 self.characters[target as usize].name
-                            );
+                            )
+                            .unwrap();
                         }
                         _ => {}
                     }
-                    let _ = self.g.getch();
+                    self.wait_key();
                     return;
                 }
             }
@@ -452,9 +586,9 @@ impl Context {
     }
 
     // [6-5]맵을 그리는 처리를 기술하는 함수를 선언한다
-    fn draw_map(&self) {
+    fn draw_map(&mut self) {
         // [6-5-1]화면을 클리어한다
-        clearscreen::clear().unwrap();
+        self.output.clear();
 
         let y0 = self.player_y as isize - SCREEN_HEIGHT as isize / 2;
         let y1 = self.player_y as isize + SCREEN_HEIGHT as isize / 2;
@@ -465,7 +599,7 @@ impl Context {
                 // [6-5-4]대상 좌표가 플레이어의 좌표와 같은지 여부를 판정한다
                 if x == self.player_x as isize && y == self.player_y as isize {
                     // [6-5-5]플레이어의 아스키아트를 그린다
-                    print!("勇");
+                    write!(self.output, "勇").unwrap();
                 } else if x < 0
                     || x >= MAP_WIDTH as isize
                     || y < 0
@@ -475,247 +609,435 @@ impl Context {
                     // [6-5-6]대상 좌표가 맵 데이터의 범위인지 여부를 판정한다
 
                     match self.current_map {
-                        MapEnum::Field => print!("~~"),      // [6-5-8]필드 밖은 바다
-                        MapEnum::KingCastle => print!(". "), // [6-5-9]왕의 성 밖은 평지
-                        MapEnum::BossCastle => print!(". "), // [6-5-10]마왕의 성 밖은 평지
+                        MapEnum::Field => write!(self.output, "~~").unwrap(), // [6-5-8]필드 밖은 바다
+                        MapEnum::KingCastle => write!(self.output, ". ").unwrap(), // [6-5-9]왕의 성 밖은 평지
+                        MapEnum::BossCastle => write!(self.output, ". ").unwrap(), // [6-5-10]마왕의 성 밖은 평지
                         _ => {}
                     }
                 } else {
                     // [6-5-11]상기의 상태가 아니면
 
                     match self.get_cell_xy(self.current_map, x as usize, y as usize) as char {
-                        '~' => print!("~~"), // [6-5-13]바다
-                        '.' => print!(". "), // [6-5-14]평지
-                        'M' => print!("MM"), // [6-5-15]산
-                        '#' => print!("##"), // [6-5-16]다리
-                        'K' => print!("王"), // [6-5-17]왕의 성
-                        'B' => print!("魔"), // [6-5-18]마왕의 성
-                        'H' => print!("□□"), // [6-5-19]벽
-                        'W' => print!("炎"), // [6-5-20]불
-                        'Y' => print!("Y "), // [6-5-21]촛대
-                        '0' => print!("王"), // [6-5-22]왕
-                        '1' => print!("姫"), // [6-5-23]공주님
-                        '2' => print!("魔"), // [6-5-24]마왕
+                        '~' => write!(self.output, "~~").unwrap(), // [6-5-13]바다
+                        '.' => write!(self.output, ". ").unwrap(), // [6-5-14]평지
+                        'M' => write!(self.output, "MM").unwrap(), // [6-5-15]산
+                        '#' => write!(self.output, "##").unwrap(), // [6-5-16]다리
+                        'K' => write!(self.output, "王").unwrap(), // [6-5-17]왕의 성
+                        'B' => write!(self.output, "魔").unwrap(), // [6-5-18]마왕의 성
+                        'H' => write!(self.output, "□□").unwrap(), // [6-5-19]벽
+                        'W' => write!(self.output, "炎").unwrap(), // [6-5-20]불
+                        'Y' => write!(self.output, "Y ").unwrap(), // [6-5-21]촛대
+                        '0' => write!(self.output, "王").unwrap(), // [6-5-22]왕
+                        '1' => write!(self.output, "姫").unwrap(), // [6-5-23]공주님
+                        '2' => write!(self.output, "魔").unwrap(), // [6-5-24]마왕
                         _ => {}
                     }
                 }
             }
-            println!();
+            writeln!(self.output).unwrap();
         }
-        println!();
+        writeln!(self.output).unwrap();
 
         // [6-5-27]플레이어의 이름을 표시한다
-        println!("{}", self.characters[CharacterEnum::Player as usize].name);
+        writeln!(
+            self.output,
+            "{}",
+            self.characters[CharacterEnum::Player as usize].name
+        )
+        .unwrap();
 
         // [6-5-28]플레이어의 상태를 표시한다
-        println!(
+        writeln!(
+            self.output,
             "HP:{}/{} MP:{}/{}",
             self.characters[CharacterEnum::Player as usize].hp,
             self.characters[CharacterEnum::Player as usize].max_hp,
             self.characters[CharacterEnum::Player as usize].mp,
             self.characters[CharacterEnum::Player as usize].max_mp,
-        );
+        )
+        .unwrap();
 
-        println!();
+        writeln!(self.output).unwrap();
     }
 
-    fn pause_a_key(&self) {
-        if let Ok(Key::Esc) = self.g.getch() {
-            std::process::exit(0);
+    // 아무 키나 눌릴 때까지 기다린다. 입력이 끝났으면 게임을 종료한다
+    fn wait_key(&mut self) {
+        if self.input.next_key().is_none() {
+            self.quit = true;
         }
     }
-}
 
-fn main() {
-    let mut ctx = Context::new();
+    // ESC가 눌리거나 입력이 끝났으면 게임을 종료한다
+    fn pause_a_key(&mut self) {
+        if let Some(Key::Esc) | None = self.input.next_key() {
+            self.quit = true;
+        }
+    }
 
-    // [6-6-2]게임을 초기화하는 함수를 호출한다
-    ctx.init();
+    // [6-6]게임의 메인 루프를 실행한다. ESC, 엔딩 또는 입력의 끝에서 반환한다
+    pub fn run(&mut self) {
+        loop {
+            // ESC가 눌렸거나 입력이 끝났다면 메인 루프를 빠져나간다
+            if self.quit {
+                return;
+            }
 
-    // [6-6-3]전투 장면의 함수를 호출한다
-    loop {
-        // [6-6-4]맵을 그리는 함수를 호출한다
-        ctx.draw_map();
-
-        // [6-6-5]플레이어의 이동 전 좌표를 선언한다
-        let last_player_x = ctx.player_x;
-        let last_player_y = ctx.player_y;
-
-        // [6-6-7]입력된 키로 분기한다
-        match ctx.g.getch() {
-            Ok(Key::Char('w')) => ctx.player_y -= 1,
-            Ok(Key::Char('s')) => ctx.player_y += 1,
-            Ok(Key::Char('a')) => ctx.player_x -= 1,
-            Ok(Key::Char('d')) => ctx.player_x += 1,
-            Ok(Key::Esc) => std::process::exit(0),
-            _ => {}
-        }
+            // [6-6-4]맵을 그리는 함수를 호출한다
+            self.draw_map();
+
+            // [6-6-5]플레이어의 이동 전 좌표를 선언한다
+            let last_player_x = self.player_x;
+            let last_player_y = self.player_y;
+
+            // [6-6-7]입력된 키로 분기한다
+            match self.input.next_key() {
+                // 0에서 위, 왼쪽으로 움직이면 맵의 밖(MAP_WIDTH 이상)으로 취급한다
+                Some(Key::Char('w')) => self.player_y = self.player_y.wrapping_sub(1),
+                Some(Key::Char('s')) => self.player_y += 1,
+                Some(Key::Char('a')) => self.player_x = self.player_x.wrapping_sub(1),
+                Some(Key::Char('d')) => self.player_x += 1,
+                Some(Key::Esc) | None => return,
+                _ => {}
+            }
 
-        // [6-6-12]맵의 밖으로 나갔는지 여부를 판정한다
-        if ctx.player_x < 0
-            || ctx.player_x >= MAP_WIDTH
-            || ctx.player_y < 0
-            || ctx.player_y >= MAP_HEIGHT
-            || ctx.get_cell_xy(
-                ctx.current_map,
-                ctx.player_x,
-                ctx.player_y,
-            ) == 0
-        {
-            // [6-6-13]현재 맵에 따라 분기한다
-            match ctx.current_map {
-                MapEnum::KingCastle => {
-                    // [6-6-14]왕의 성
+            // [6-6-12]맵의 밖으로 나갔는지 여부를 판정한다
+            if self.player_x >= MAP_WIDTH
+                || self.player_y >= MAP_HEIGHT
+                || self.get_cell_xy(self.current_map, self.player_x, self.player_y) == 0
+            {
+                // [6-6-13]현재 맵에 따라 분기한다
+                match self.current_map {
+                    MapEnum::KingCastle => {
+                        // [6-6-14]왕의 성
+
+                        // [6-6-15]필드 맵으로 전환한다
+                        self.current_map = MapEnum::Field;
+
+                        self.player_x = 6;
+                        self.player_y = 12;
+                    }
+                    MapEnum::BossCastle => {
+                        // [6-6-18]마왕의 성
+
+                        // [6-6-19]필드 맵으로 전환한다
+                        self.current_map = MapEnum::Field;
+
+                        self.player_x = 10;
+                        self.player_y = 9;
+                    }
+                    _ => {}
+                }
+            }
 
-                    // [6-6-15]필드 맵으로 전환한다
-                    ctx.current_map = MapEnum::Field;
+            // [6-6-22]이동 목적지 칸의 종류에 따라 분기시킨다
+            match self.get_cell_xy(self.current_map, self.player_x, self.player_y) as char {
+                'K' => {
+                    // [6-6-23]왕의 성
 
-                    ctx.player_x = 6;
-                    ctx.player_y = 12;
+                    // [6-6-24]왕의 성으로 맵을 전환한다
+                    self.current_map = MapEnum::KingCastle;
+
+                    self.player_x = 6;
+                    self.player_y = 12;
+                }
+                '0' => {
+                    // [6-6-31]왕
+
+                    // [6-6-32]왕의 대화 메시지를 표시한다
+                    writeln!(
+                        self.output,
+                        "*[용사여!\n\
+                        동쪽 마성의 마왕을\n\
+                        물리치고 세상을 구해주세요!"
+                    )
+                    .unwrap();
+
+                    self.pause_a_key();
                 }
-                MapEnum::BossCastle => {
-                    // [6-6-18]마왕의 성
+                '1' => {
+                    // [6-6-34]공주
+
+                    // [6-6-35]공주의 대화 메시지를 표시한다
+                    writeln!(
+                        self.output,
+                        "*[신께 기도드립니다.!\n\
+                        오, 신이시여!\n\
+                        용사님에게 축복을!"
+                    )
+                    .unwrap();
+
+                    self.pause_a_key();
+
+                    // [6-6-37]플레이어의 HP를 회복시킨다
+                    self.characters[CharacterEnum::Player as usize].hp =
+                        self.characters[CharacterEnum::Player as usize].max_hp;
+
+                    // [6-6-38]플레이어의 MP를 회복시킨다
+                    self.characters[CharacterEnum::Player as usize].mp =
+                        self.characters[CharacterEnum::Player as usize].max_mp;
+                }
+                'B' => {
+                    // [6-6-27]마왕의 성
 
-                    // [6-6-19]필드 맵으로 전환한다
-                    ctx.current_map = MapEnum::Field;
+                    // [6-6-28]마왕의 성으로 맵을 전환한다
+                    self.current_map = MapEnum::BossCastle;
 
-                    ctx.player_x = 10;
-                    ctx.player_y = 9;
+                    self.player_x = 6;
+                    self.player_y = 15;
                 }
+                '2' => {
+                    // [6-6-39]마왕
+
+                    // [6-6-40]마왕의 대화 메시지를 표시한다
+                    writeln!(
+                        self.output,
+                        "*어리석은 인간이여!\n\
+                        나의 야망을 방해하는 자는\n\
+                        이 세상에서 사라지게 될 것이다!"
+                    )
+                    .unwrap();
+
+                    self.pause_a_key();
+
+                    // [6-6-42]마왕과의 전투를 발생시킨다
+                    self.battle(MonsterEnum::Boss);
+
+                    // [6-6-43]마왕이 죽었는지 여부를 판정한다
+                    if self.characters[CharacterEnum::Monster as usize].hp <= 0 {
+                        self.output.clear();
+
+                        // [6-6-45]엔딩 메시지를 표시한다
+                        writeln!(
+                            self.output,
+                            "  마왕은 사라지고 세상은\n\
+                            멸망의 위기에서 구원되다!\n\
+                            \n\
+                             왕은 포고령을 내리고 용사를\n\
+                            찾아 헤맸지만, 아무도\n\
+                            본 사람은 없었다고 전해진다...\n\
+                            \n\
+                            \n\
+                                      THE END"
+                        )
+                        .unwrap();
+
+                        self.pause_a_key();
+
+                        return;
+                    }
+                }
+
                 _ => {}
             }
-        }
 
-        // [6-6-22]이동 목적지 칸의 종류에 따라 분기시킨다
-        match ctx.get_cell_xy(ctx.current_map, ctx.player_x, ctx.player_y) as char {
-            'K' => {
-                // [6-6-23]왕의 성
+            // [6-6-48]이동 목적지 칸의 종류에 따라 분기시킨다
+            match self.get_cell_xy(self.current_map, self.player_x, self.player_y) as char {
+                '.' | '#' => {
+                    // [6-6-49]평지
+                    // [6-6-50]다리
 
-                // [6-6-24]왕의 성으로 맵을 전환한다
-                ctx.current_map = MapEnum::KingCastle;
+                    // [6-6-51]적과 조우했는지 여부를 판정한다
+                    if self.current_map == MapEnum::Field && self.rng.random_range(0..16) == 0 {
+                        // [6-6-52]잡어 몬스터와의 전투를 발생시킨다
+                        self.battle(MonsterEnum::Slime);
+                    }
+                }
+                _ => {
+                    // [6-6-53]상기 이외의 칸
 
-                ctx.player_x = 6;
-                ctx.player_y = 12;
+                    // [6-6-54]플레이어의 좌표를 이동 전으로 되돌린다
+                    self.player_x = last_player_x;
+                    self.player_y = last_player_y;
+                }
             }
-            '0' => {
-                // [6-6-31]왕
 
-                // [6-6-32]왕의 대화 메시지를 표시한다
-                println!(
-                    "*[용사여!\n\
-                    동쪽 마성의 마왕을\n\
-                    물리치고 세상을 구해주세요!"
-                );
+            // [6-6-56]플레이어가 사망했는지 여부를 판정한다
+            if self.characters[CharacterEnum::Player as usize].hp <= 0 {
+                // [6-6-57]게임을 초기화하는 함수를 호출한다
+                self.init();
+
+                // [6-6-58]화면을 다시 그린다
+                self.draw_map();
 
-                ctx.pause_a_key();
+                // [6-6-59]왕의 메시지를 표시한다
+                writeln!(
+                    self.output,
+                    "* 오 용사여!\n\
+                    신이 그대를 구원하셨다!\n\
+                    용사에게 영광을!"
+                )
+                .unwrap();
+
+                self.pause_a_key();
             }
-            '1' => {
-                // [6-6-34]공주
+        }
+    }
+}
 
-                // [6-6-35]공주의 대화 메시지를 표시한다
-                println!(
-                    "*[신께 기도드립니다.!\n\
-                    오, 신이시여!\n\
-                    용사님에게 축복을!"
-                );
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+
+    // [6-6-1]「--script 키열 [시드]」가 주어지면 키보드 대신 키열로 게임을 진행한다
+    if args.len() >= 3 && args[1] == "--script" {
+        let seed = args.get(3).and_then(|s| s.parse().ok()).unwrap_or(0);
+        let mut ctx = Context::new(
+            ScriptedInput::new(&args[2]),
+            FrameRecorder::default(),
+            StdRng::seed_from_u64(seed),
+        );
+        ctx.init();
+        ctx.run();
 
-                ctx.pause_a_key();
+        // 마지막 프레임과 플레이어의 위치를 표시한다
+        if let Some(frame) = ctx.output.frames.last() {
+            print!("{}", frame);
+        }
+        println!(
+            "frames:{} map:{:?} x:{} y:{}",
+            ctx.output.frames.len(),
+            ctx.current_map,
+            ctx.player_x,
+            ctx.player_y
+        );
+        return;
+    }
 
-                // [6-6-37]플레이어의 HP를 회복시킨다
-                ctx.characters[CharacterEnum::Player as usize].hp =
-                    ctx.characters[CharacterEnum::Player as usize].max_hp;
+    let mut ctx = Context::new(Getch::new(), Terminal, StdRng::from_os_rng());
 
-                // [6-6-38]플레이어의 MP를 회복시킨다
-                ctx.characters[CharacterEnum::Player as usize].mp =
-                    ctx.characters[CharacterEnum::Player as usize].max_mp;
-            }
-            'B' => {
-                // [6-6-27]마왕의 성
+    // [6-6-2]게임을 초기화하는 함수를 호출한다
+    ctx.init();
 
-                // [6-6-28]마왕의 성으로 맵을 전환한다
-                ctx.current_map = MapEnum::BossCastle;
+    // [6-6-3]메인 루프를 실행한다
+    ctx.run();
+}
 
-                ctx.player_x = 6;
-                ctx.player_y = 15;
-            }
-            '2' => {
-                // [6-6-39]마왕
-
-                // [6-6-40]마왕의 대화 메시지를 표시한다
-                println!(
-                    "*어리석은 인간이여!\n\
-                    나의 야망을 방해하는 자는\n\
-                    이 세상에서 사라지게 될 것이다!"
-                );
-
-                ctx.pause_a_key();
-
-                // [6-6-42]마왕과의 전투를 발생시킨다
-                ctx.battle(MonsterEnum::Boss);
-
-                // [6-6-43]마왕이 죽었는지 여부를 판정한다
-                if ctx.characters[CharacterEnum::Monster as usize].hp <= 0 {
-                    clearscreen::clear().unwrap();
-
-                    // [6-6-45]엔딩 메시지를 표시한다
-                    println!(
-                        "  마왕은 사라지고 세상은\n\
-                        멸망의 위기에서 구원되다!\n\
-                        \n\
-                         왕은 포고령을 내리고 용사를\n\
-                        찾아 헤맸지만, 아무도\n\
-                        본 사람은 없었다고 전해진다...\n\
-                        \n\
-                        \n\
-                                  THE END"
-                    );
-
-                    ctx.pause_a_key();
-
-                    std::process::exit(0);
-                }
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            _ => {}
-        }
+    // 왕의 성을 나와 필드를 지나 마왕의 성 앞까지 가는 동안 잡어 몬스터와 만나지 않는 시드
+    const SEED: u64 = 11;
 
-        // [6-6-48]이동 목적지 칸의 종류에 따라 분기시킨다
-        match ctx.get_cell_xy(ctx.current_map, ctx.player_x, ctx.player_y) as char {
-            '.' | '#' => {
-                // [6-6-49]평지
-                // [6-6-50]다리
+    // 성의 출구에서 마왕의 성(B)까지 필드를 걷는 키열
+    const FIELD_ROUTE: &str = "saaaawwwwdwwwawwwwddssddwwddddssddwwddsssasssdsssssasaaawwddwwaaww";
 
-                // [6-6-51]적과 조우했는지 여부를 판정한다
-                if ctx.current_map == MapEnum::Field && ctx.rng.random_range(0..16) == 0 {
-                    // [6-6-52]잡어 몬스터와의 전투를 발생시킨다
-                    ctx.battle(MonsterEnum::Slime);
-                }
-            }
-            _ => {
-                // [6-6-53]상기 이외의 칸
+    fn new_context() -> Context<ScriptedInput, FrameRecorder> {
+        let mut ctx = Context::new(
+            ScriptedInput::new(""),
+            FrameRecorder::default(),
+            StdRng::seed_from_u64(SEED),
+        );
+        ctx.init();
+        ctx
+    }
 
-                // [6-6-54]플레이어의 좌표를 이동 전으로 되돌린다
-                ctx.player_x = last_player_x;
-                ctx.player_y = last_player_y;
-            }
-        }
+    // 키열을 입력이 끝날 때까지 진행하고 새로 그려진 프레임을 반환한다
+    fn play(ctx: &mut Context<ScriptedInput, FrameRecorder>, script: &str) -> Vec<String> {
+        let start = ctx.output.frames.len();
+        ctx.input = ScriptedInput::new(script);
+        ctx.run();
+        ctx.output.frames[start..].to_vec()
+    }
+
+    fn assert_position(
+        ctx: &Context<ScriptedInput, FrameRecorder>,
+        map: MapEnum,
+        x: usize,
+        y: usize,
+    ) {
+        assert_eq!(ctx.current_map, map);
+        assert_eq!((ctx.player_x, ctx.player_y), (x, y));
+    }
 
-        // [6-6-56]플레이어가 사망했는지 여부를 판정한다
-        if ctx.characters[CharacterEnum::Player as usize].hp <= 0 {
-            // [6-6-57]게임을 초기화하는 함수를 호출한다
-            ctx.init();
+    #[test]
+    fn walk_from_king_to_boss_castle() {
+        let mut ctx = new_context();
+        assert_position(&ctx, MapEnum::KingCastle, 4, 6);
+
+        // 위의 왕에게 말을 걸면 대화가 표시되고 그 자리에 머문다
+        let frames = play(&mut ctx, "w ");
+        assert!(frames[0].contains("勇"));
+        assert!(frames[0].contains("동쪽 마성의 마왕을"));
+        assert!(!frames[1].contains("동쪽 마성의 마왕을"));
+        assert_position(&ctx, MapEnum::KingCastle, 4, 6);
+        assert!(!ctx.quit);
+
+        // 다리를 건너 성 밖으로 나가면 필드의 왕의 성 아래에 선다
+        play(&mut ctx, "dd");
+        assert_position(&ctx, MapEnum::KingCastle, 6, 6);
+        let frames = play(&mut ctx, "ssssss");
+        assert_position(&ctx, MapEnum::Field, 6, 12);
+        let last = frames.last().unwrap();
+        assert!(last.contains("王"));
+        assert!(last.contains("~~"));
+        assert!(last.contains("HP:100/100 MP:15/15"));
+
+        // 필드를 걸어 마왕의 성에 들어간다
+        let frames = play(&mut ctx, FIELD_ROUTE);
+        assert!(frames.iter().all(|frame| !frame.contains("나타났다")));
+        assert_position(&ctx, MapEnum::BossCastle, 6, 15);
+        assert!(frames.last().unwrap().contains("□□"));
+        assert!(!ctx.quit);
+    }
 
-            // [6-6-58]화면을 다시 그린다
-            ctx.draw_map();
+    #[test]
+    fn walls_block_movement() {
+        let mut ctx = new_context();
 
-            // [6-6-59]왕의 메시지를 표시한다
-            println!(
-                "* 오 용사여!\n\
-                신이 그대를 구원하셨다!\n\
-                용사에게 영광을!"
-            );
+        // 왼쪽은 벽이므로 움직이지 않는다
+        play(&mut ctx, "aa");
+        assert_position(&ctx, MapEnum::KingCastle, 2, 6);
+        play(&mut ctx, "a");
+        assert_position(&ctx, MapEnum::KingCastle, 2, 6);
+    }
 
-            ctx.pause_a_key();
-        }
+    #[test]
+    fn escape_while_talking_quits() {
+        let mut ctx = new_context();
+
+        // 왕의 대화를 ESC로 닫으면 게임이 종료된다
+        let frames = play(&mut ctx, "w\x1bddd");
+        assert!(ctx.quit);
+        assert_eq!(frames.len(), 1);
+        assert_position(&ctx, MapEnum::KingCastle, 4, 6);
+    }
+
+    #[test]
+    fn end_of_input_in_battle_quits() {
+        let mut ctx = new_context();
+        ctx.current_map = MapEnum::BossCastle;
+        ctx.player_x = 6;
+        ctx.player_y = 6;
+
+        // 마왕의 대화 뒤 전투의 첫 메시지에서 입력이 끝나면 게임이 종료된다
+        let frames = play(&mut ctx, "w ");
+        assert!(ctx.quit);
+        assert!(
+            frames
+                .iter()
+                .any(|frame| frame.contains("마왕이(가) 나타났다!"))
+        );
+    }
+
+    #[test]
+    fn defeating_boss_shows_ending() {
+        let mut ctx = new_context();
+        ctx.monsters[MonsterEnum::Boss as usize].hp = 1;
+        ctx.current_map = MapEnum::BossCastle;
+        ctx.player_x = 6;
+        ctx.player_y = 15;
+
+        // 마왕에게 다가가 대화, 등장, 명령 선택, 공격, 대미지, 쓰러뜨림, 엔딩의 순으로 키를 누른다
+        let frames = play(&mut ctx, "wwwwwwwwww       ");
+        assert!(
+            frames
+                .iter()
+                .any(|frame| frame.contains("마왕을(를) 쓰러뜨렸다!"))
+        );
+        let last = frames.last().unwrap();
+        assert!(last.contains("THE END"));
+        assert!(!last.contains("勇"));
+        assert!(!ctx.quit);
+        assert_eq!(ctx.characters[CharacterEnum::Monster as usize].hp, 0);
     }
 }